@@ -0,0 +1,35 @@
+use npe::bvh::{self, Bvh, Camera};
+use nuitrack_pose_estimation as npe;
+use std::env;
+use std::io;
+
+fn main() -> io::Result<()> {
+    let mut args = env::args();
+    args.next();
+    let path = match args.next() {
+        Some(p) => p,
+        None => {
+            println!("Usage: bvh_template <motion.bvh> [frame]");
+            return Ok(());
+        }
+    };
+    let frame = args.next().and_then(|f| f.parse().ok()).unwrap_or(0);
+
+    let bvh = Bvh::load(&path)?;
+    let positions = bvh
+        .world_positions(frame)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No such frame"))?;
+    let camera = Camera::framing(positions.values(), 4.0 / 3.0);
+    let template = bvh
+        .template(frame, &camera, &bvh::NAMES)
+        .expect("Frame was checked");
+
+    // Printed in the same layout as src/poses.rs
+    for joint in npe::ARMS.iter() {
+        match template.get(joint) {
+            Some(v) => println!("(JointType::{:?}, [{}, {}]),", joint, v.x, v.y),
+            None => eprintln!("Missing {:?}, check the BVH joint names", joint),
+        }
+    }
+    Ok(())
+}
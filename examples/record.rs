@@ -0,0 +1,33 @@
+use nuitrack_pose_estimation as npe;
+use std::env;
+use std::io;
+
+fn main() -> io::Result<()> {
+    let mut args = env::args();
+    args.next();
+    let (input, output) = match (args.next(), args.next()) {
+        (Some(i), Some(o)) => (i, o),
+        _ => {
+            println!("Usage: record <nuitrack recording> <output.jsonl>");
+            return Ok(());
+        }
+    };
+
+    let mut recorder = npe::recording::Recorder::create(&output)?;
+
+    let mut nui = nuitrack_rs::playback(input, false).expect("Failed to make player");
+
+    nui.skeleton_data(move |data| {
+        let skeletons: Vec<npe::Skeleton> = data.skeletons().map(npe::Skeleton::from).collect();
+        recorder
+            .write(&skeletons)
+            .and_then(|_| recorder.flush())
+            .expect("Failed to write recording");
+    })
+    .expect("Failed to create skeleton callback");
+
+    println!("Recording to {}, press Ctrl-C to stop", output);
+    loop {
+        nui.update().expect("failed to update nui player");
+    }
+}
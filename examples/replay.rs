@@ -0,0 +1,35 @@
+use nuitrack_pose_estimation as npe;
+use std::env;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+fn main() -> io::Result<()> {
+    let mut args = env::args();
+    args.next();
+    let path = match args.next() {
+        Some(p) => p,
+        None => {
+            println!("Usage: replay <recording.jsonl> [--realtime]");
+            return Ok(());
+        }
+    };
+    let realtime = args.next().map(|a| a == "--realtime").unwrap_or(false);
+
+    let detector = npe::Detector::default();
+    let mut last = 0.0;
+
+    for frame in npe::recording::Reader::open(&path)? {
+        let frame = frame?;
+        if realtime && frame.timestamp > last {
+            thread::sleep(Duration::from_millis(
+                ((frame.timestamp - last) * 1000.0) as u64,
+            ));
+        }
+        last = frame.timestamp;
+        for (id, pose) in frame.detect(&detector) {
+            println!("{:.3} skeleton {}: {:?}", frame.timestamp, id, pose);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,202 @@
+/*
+ * C interface to nuitrack_pose_estimation, generated from src/ffi.rs
+ * by cbindgen. Don't edit it by hand.
+ * Build the library with
+ * `cargo rustc --lib --release --no-default-features --features ffi --crate-type staticlib`
+ * (or `cdylib`) and link libnuitrack_pose_estimation.a or the shared library.
+ *
+ * Ownership:
+ * - A detector made with npe_detector_new is owned by the caller and
+ *   must be freed once with npe_detector_free.
+ * - Strings and arrays passed in are only borrowed for the call.
+ * - npe_pose_name returns static strings. npe_last_error returns a string
+ *   owned by the library that is valid until the next call on that thread.
+ *   Neither should be freed.
+ *
+ * A detector can be shared between threads.
+ * Functions that can fail return an NPE_* status and write their
+ * results through out pointers.
+ */
+
+#ifndef NUITRACK_POSE_ESTIMATION_H
+#define NUITRACK_POSE_ESTIMATION_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Detects poses in skeletons. It's opaque and made with npe_detector_new
+ */
+typedef struct npe_detector npe_detector;
+
+#define NPE_OK 0
+
+/**
+ * A required pointer was null
+ */
+#define NPE_NULL_POINTER -1
+
+/**
+ * An argument was out of range or not valid UTF-8
+ */
+#define NPE_INVALID_ARGUMENT -2
+
+/**
+ * A pose library couldn't be read or parsed
+ */
+#define NPE_LIBRARY_ERROR -3
+
+/**
+ * Something went wrong inside the library
+ */
+#define NPE_PANIC -4
+
+/**
+ * Written to `pose_out` when no pose was detected
+ */
+#define NPE_NO_POSE -1
+
+/**
+ * Poses, as written to `pose_out`
+ */
+#define NPE_POSE_DAB_R 0
+
+#define NPE_POSE_DAB_L 1
+
+#define NPE_POSE_HANDS_UP 2
+
+#define NPE_POSE_ROOF 3
+
+#define NPE_POSE_FLYING_R 4
+
+#define NPE_POSE_FLYING_L 5
+
+/**
+ * Joint types, the same as Nuitrack's
+ */
+#define NPE_JOINT_HEAD 1
+
+#define NPE_JOINT_NECK 2
+
+#define NPE_JOINT_TORSO 3
+
+#define NPE_JOINT_WAIST 4
+
+#define NPE_JOINT_LEFT_COLLAR 5
+
+#define NPE_JOINT_LEFT_SHOULDER 6
+
+#define NPE_JOINT_LEFT_ELBOW 7
+
+#define NPE_JOINT_LEFT_WRIST 8
+
+#define NPE_JOINT_LEFT_HAND 9
+
+#define NPE_JOINT_LEFT_FINGERTIP 10
+
+#define NPE_JOINT_RIGHT_COLLAR 11
+
+#define NPE_JOINT_RIGHT_SHOULDER 12
+
+#define NPE_JOINT_RIGHT_ELBOW 13
+
+#define NPE_JOINT_RIGHT_WRIST 14
+
+#define NPE_JOINT_RIGHT_HAND 15
+
+#define NPE_JOINT_RIGHT_FINGERTIP 16
+
+#define NPE_JOINT_LEFT_HIP 17
+
+#define NPE_JOINT_LEFT_KNEE 18
+
+#define NPE_JOINT_LEFT_ANKLE 19
+
+#define NPE_JOINT_LEFT_FOOT 20
+
+#define NPE_JOINT_RIGHT_HIP 21
+
+#define NPE_JOINT_RIGHT_KNEE 22
+
+#define NPE_JOINT_RIGHT_ANKLE 23
+
+#define NPE_JOINT_RIGHT_FOOT 24
+
+typedef struct npe_settings {
+  float joint_cutoff;
+  float rotation_cutoff;
+} npe_settings;
+
+/**
+ * A joint in image coordinates.
+ * `joint_type` uses Nuitrack's joint ids
+ */
+typedef struct npe_joint {
+  uint32_t joint_type;
+  float x;
+  float y;
+  float confidence;
+} npe_joint;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Make a detector with the default settings and poses.
+ * Free it with `npe_detector_free`. Returns null on failure
+ */
+npe_detector *npe_detector_new(void);
+
+/**
+ * Free a detector. Null is ignored
+ */
+void npe_detector_free(npe_detector *detector);
+
+/**
+ * Replace the poses with a library read from a JSON file
+ */
+int32_t npe_detector_load_poses_file(const npe_detector *detector, const char *path);
+
+/**
+ * Replace the poses with a library from a JSON string
+ */
+int32_t npe_detector_load_poses_str(const npe_detector *detector, const char *json);
+
+/**
+ * Set the cutoffs. They must be positive
+ */
+int32_t npe_detector_set_settings(const npe_detector *detector,
+                                  const struct npe_settings *settings);
+
+int32_t npe_detector_get_settings(const npe_detector *detector, struct npe_settings *settings_out);
+
+/**
+ * Detect a pose in `count` joints.
+ * Writes the pose or `NPE_NO_POSE` to `pose_out`.
+ * `score_out` may be null, otherwise it gets the score of the
+ * closest pose from 0 to 1, see `PoseReport::score`
+ */
+int32_t npe_detector_detect(const npe_detector *detector,
+                            const struct npe_joint *joints,
+                            size_t count,
+                            int32_t *pose_out,
+                            float *score_out);
+
+/**
+ * Name of a pose, or null if there's no such pose.
+ * The string is static and mustn't be freed
+ */
+const char *npe_pose_name(int32_t pose);
+
+/**
+ * Message for the last error on this thread, or null.
+ * Valid until the next call on this thread
+ */
+const char *npe_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* NUITRACK_POSE_ESTIMATION_H */
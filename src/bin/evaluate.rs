@@ -0,0 +1,59 @@
+//! Run a detector over labelled skeletons and report how it did.
+//!
+//! Usage: evaluate <labelled.jsonl> [--settings <settings.json>]
+//!                 [--joint-cutoff <f32>] [--rotation-cutoff <f32>] [--json]
+
+use nuitrack_pose_estimation as npe;
+use std::env;
+use std::process;
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let mut path = None;
+    let mut settings = npe::Settings::default();
+    let mut json = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--settings" => {
+                let file = args.next().unwrap_or_else(|| usage());
+                settings = npe::Settings::load(&file).unwrap_or_else(|e| fail(&file, e));
+            }
+            "--joint-cutoff" => settings.joint_cutoff = number(args.next()),
+            "--rotation-cutoff" => settings.rotation_cutoff = number(args.next()),
+            "--json" => json = true,
+            _ if path.is_none() => path = Some(arg),
+            _ => usage(),
+        }
+    }
+    let path = path.unwrap_or_else(|| usage());
+
+    let frames = npe::evaluate::load(&path).unwrap_or_else(|e| fail(&path, e));
+    let detector = npe::Detector::new(settings);
+    let evaluation = npe::evaluate::evaluate(&detector, &frames);
+
+    if json {
+        let out = serde_json::to_string_pretty(&evaluation).expect("Failed to write json");
+        println!("{}", out);
+    } else {
+        println!("{:?}\n", detector.settings());
+        print!("{}", evaluation);
+    }
+}
+
+fn number(arg: Option<String>) -> f32 {
+    arg.and_then(|a| a.parse().ok()).unwrap_or_else(|| usage())
+}
+
+fn fail<E: std::fmt::Display>(what: &str, e: E) -> ! {
+    eprintln!("Failed to read {}: {}", what, e);
+    process::exit(1);
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: evaluate <labelled.jsonl> [--settings <settings.json>] \
+         [--joint-cutoff <f32>] [--rotation-cutoff <f32>] [--json]"
+    );
+    process::exit(2);
+}
@@ -0,0 +1,101 @@
+//! Replay a recording and stream its detections to WebSocket clients.
+//! Clients can change the cutoffs and poses while it runs,
+//! see `src/websocket.rs` for the messages.
+//! With `--watch` the settings and poses files are
+//! reloaded when they change.
+//!
+//! Usage: pose_server <recording.jsonl> [--addr <host:port>] [--settings <settings.json>]
+//!                    [--poses <library.json>] [--loop] [--watch]
+
+use npe::recording::Reader;
+use npe::reload::Reloader;
+use npe::websocket::Server;
+use nuitrack_pose_estimation as npe;
+use std::env;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let mut path = None;
+    let mut addr = "127.0.0.1:9001".to_string();
+    let mut settings = npe::Settings::default();
+    let mut library = None;
+    let mut watched = (None, None);
+    let mut repeat = false;
+    let mut watch = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => addr = args.next().unwrap_or_else(|| usage()),
+            "--settings" => {
+                let file = args.next().unwrap_or_else(|| usage());
+                settings = npe::Settings::load(&file).unwrap_or_else(|e| fail(&file, e));
+                watched.0 = Some(file);
+            }
+            "--poses" => {
+                let file = args.next().unwrap_or_else(|| usage());
+                library = Some(npe::library::load(&file).unwrap_or_else(|e| fail(&file, e)));
+                watched.1 = Some(file);
+            }
+            "--loop" => repeat = true,
+            "--watch" => watch = true,
+            _ if path.is_none() => path = Some(arg),
+            _ => usage(),
+        }
+    }
+    let path = path.unwrap_or_else(|| usage());
+
+    let detector = Arc::new(match library {
+        Some(library) => npe::Detector::with_library(settings, library),
+        None => npe::Detector::new(settings),
+    });
+    let _watcher = if watch {
+        let mut reloader = Reloader::new(detector.clone());
+        if let Some(file) = watched.0 {
+            reloader.watch_settings(file);
+        }
+        if let Some(file) = watched.1 {
+            reloader.watch_poses(file);
+        }
+        Some(reloader.spawn(Duration::from_millis(500)))
+    } else {
+        None
+    };
+    let mut server = Server::bind(&addr, detector).unwrap_or_else(|e| fail(&addr, e));
+    println!("Serving on ws://{}", server.local_addr());
+
+    loop {
+        let mut last = 0.0;
+        for frame in Reader::open(&path).unwrap_or_else(|e| fail(&path, e)) {
+            let frame = frame.unwrap_or_else(|e| fail(&path, e));
+            if frame.timestamp > last {
+                thread::sleep(Duration::from_millis(
+                    ((frame.timestamp - last) * 1000.0) as u64,
+                ));
+            }
+            last = frame.timestamp;
+            for event in server.send_frame(&frame.skeletons) {
+                println!("{:?}", event);
+            }
+        }
+        if !repeat {
+            break;
+        }
+    }
+}
+
+fn fail<E: std::fmt::Display>(what: &str, e: E) -> ! {
+    eprintln!("Failed to read {}: {}", what, e);
+    process::exit(1);
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: pose_server <recording.jsonl> [--addr <host:port>] \
+         [--settings <settings.json>] [--poses <library.json>] [--loop] [--watch]"
+    );
+    process::exit(2);
+}
@@ -0,0 +1,97 @@
+//! Search for the cutoffs that do best on labelled skeletons
+//! and save them as settings.
+//! Everything in `--settings` other than the cutoffs is kept.
+//!
+//! Usage: tune <labelled.jsonl> [--settings <settings.json>] [--poses <library.json>]
+//!             [--out <settings.json>] [--max-false-positives <rate>]
+//!             [--joint-cutoffs <start:end:count>] [--rotation-cutoffs <start:end:count>]
+//!             [--per-pose]
+
+use npe::tune::{steps, Objective, Tuner};
+use nuitrack_pose_estimation as npe;
+use std::env;
+use std::process;
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let mut path = None;
+    let mut out = None;
+    let mut settings = npe::Settings::default();
+    let mut library = npe::Library::default();
+    let mut tuner = Tuner::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--settings" => {
+                let file = args.next().unwrap_or_else(|| usage());
+                settings = npe::Settings::load(&file).unwrap_or_else(|e| fail(&file, e));
+            }
+            "--poses" => {
+                let file = args.next().unwrap_or_else(|| usage());
+                library = npe::library::load(&file).unwrap_or_else(|e| fail(&file, e));
+            }
+            "--out" => out = Some(args.next().unwrap_or_else(|| usage())),
+            "--max-false-positives" => {
+                let cap = args.next().and_then(|a| a.parse().ok());
+                tuner.objective =
+                    Objective::MacroF1WithFalsePositiveCap(cap.unwrap_or_else(|| usage()));
+            }
+            "--joint-cutoffs" => tuner.joint_cutoffs = range(args.next()),
+            "--rotation-cutoffs" => tuner.rotation_cutoffs = range(args.next()),
+            "--per-pose" => tuner.per_pose = true,
+            _ if path.is_none() => path = Some(arg),
+            _ => usage(),
+        }
+    }
+    let path = path.unwrap_or_else(|| usage());
+
+    let frames = npe::evaluate::load(&path).unwrap_or_else(|e| fail(&path, e));
+    let detector = npe::Detector::try_with_library(settings, library).unwrap_or_else(|e| {
+        eprintln!("Invalid settings or poses: {}", e);
+        process::exit(1);
+    });
+    let tuned = match tuner.tune(&detector, &frames) {
+        Some(tuned) => tuned,
+        None => {
+            eprintln!("No cutoffs met the objective");
+            process::exit(1);
+        }
+    };
+
+    println!("{:?}\n", tuned.settings);
+    print!("{}", tuned.evaluation);
+    if let Some(out) = out {
+        tuned.settings.save(&out).unwrap_or_else(|e| {
+            eprintln!("Failed to write {}: {}", out, e);
+            process::exit(1);
+        });
+        println!("\nSaved settings to {}", out);
+    }
+}
+
+/// Parse `start:end:count`
+fn range(arg: Option<String>) -> Vec<f32> {
+    let arg = arg.unwrap_or_else(|| usage());
+    let parts: Vec<&str> = arg.split(':').collect();
+    match parts.as_slice() {
+        [start, end, count] => match (start.parse(), end.parse(), count.parse()) {
+            (Ok(start), Ok(end), Ok(count)) => steps(start, end, count),
+            _ => usage(),
+        },
+        _ => usage(),
+    }
+}
+
+fn fail<E: std::fmt::Display>(what: &str, e: E) -> ! {
+    eprintln!("Failed to read {}: {}", what, e);
+    process::exit(1);
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "Usage: tune <labelled.jsonl> [--settings <settings.json>] [--poses <library.json>] \
+         [--out <settings.json>] [--max-false-positives <rate>] \
+         [--joint-cutoffs <start:end:count>] [--rotation-cutoffs <start:end:count>] [--per-pose]"
+    );
+    process::exit(2);
+}
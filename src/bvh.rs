@@ -0,0 +1,360 @@
+//! Make templates from BVH motion capture.
+//!
+//! A frame is posed with forward kinematics, then projected
+//! through a virtual camera into the same 0 to 1 image space
+//! as Nuitrack's `proj`. BVH joints are matched to Nuitrack
+//! joints by name, see `NAMES` for the usual ones.
+
+use crate::skeleton::add_hands;
+use crate::{glm, invalid_data, na, Joint, JointPos, JointType};
+use glm::Vec3;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+/// BVH names for Nuitrack joints as used by Mixamo and the
+/// CMU conversions. Namespaces like `mixamorig:` are ignored.
+/// Hands aren't in BVH so they're carried on from the wrists
+pub const NAMES: [(JointType, &str); 20] = [
+    (JointType::Head, "Head"),
+    (JointType::Neck, "Neck"),
+    (JointType::Torso, "Spine1"),
+    (JointType::Waist, "Hips"),
+    (JointType::LeftCollar, "LeftShoulder"),
+    (JointType::LeftShoulder, "LeftArm"),
+    (JointType::LeftElbow, "LeftForeArm"),
+    (JointType::LeftWrist, "LeftHand"),
+    (JointType::RightCollar, "RightShoulder"),
+    (JointType::RightShoulder, "RightArm"),
+    (JointType::RightElbow, "RightForeArm"),
+    (JointType::RightWrist, "RightHand"),
+    (JointType::LeftHip, "LeftUpLeg"),
+    (JointType::LeftKnee, "LeftLeg"),
+    (JointType::LeftAnkle, "LeftFoot"),
+    (JointType::LeftFoot, "LeftToeBase"),
+    (JointType::RightHip, "RightUpLeg"),
+    (JointType::RightKnee, "RightLeg"),
+    (JointType::RightAnkle, "RightFoot"),
+    (JointType::RightFoot, "RightToeBase"),
+];
+
+/// A parsed BVH file
+#[derive(Clone, Debug)]
+pub struct Bvh {
+    joints: Vec<BvhJoint>,
+    frames: Vec<Vec<f32>>,
+    /// Seconds per frame
+    pub frame_time: f32,
+}
+
+#[derive(Clone, Debug)]
+struct BvhJoint {
+    name: String,
+    parent: Option<usize>,
+    offset: Vec3,
+    channels: Vec<Channel>,
+    /// Index of this joint's first channel in a frame
+    first_channel: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Channel {
+    Position(usize),
+    Rotation(usize),
+}
+
+/// A pinhole camera looking at the skeleton
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Camera {
+    pub position: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    /// Vertical field of view in radians
+    pub fov: f32,
+    /// Image width over height
+    pub aspect: f32,
+}
+
+impl Bvh {
+    /// Load a BVH file
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut text = String::new();
+        BufReader::new(File::open(path)?).read_to_string(&mut text)?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut parser = Parser {
+            tokens: text
+                .lines()
+                .enumerate()
+                .flat_map(|(n, line)| line.split_whitespace().map(move |t| (n + 1, t)))
+                .collect(),
+            next: 0,
+            joints: Vec::new(),
+            channels: 0,
+        };
+        parser.expect("HIERARCHY")?;
+        parser.expect("ROOT")?;
+        parser.joint(None)?;
+        parser.expect("MOTION")?;
+        parser.expect("Frames:")?;
+        let count = parser.number()? as usize;
+        parser.expect("Frame")?;
+        parser.expect("Time:")?;
+        let frame_time = parser.number()?;
+        let frames = (0..count)
+            .map(|_| (0..parser.channels).map(|_| parser.number()).collect())
+            .collect::<io::Result<_>>()?;
+        Ok(Bvh {
+            joints: parser.joints,
+            frames,
+            frame_time,
+        })
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Names of the joints in the file, parents first
+    pub fn joint_names<'a>(&'a self) -> impl Iterator<Item = &'a str> + 'a {
+        self.joints.iter().map(|j| j.name.as_str())
+    }
+
+    /// Where every joint is in the world in this frame.
+    /// Returns None if the frame doesn't exist
+    pub fn world_positions(&self, frame: usize) -> Option<HashMap<String, Vec3>> {
+        let values = self.frames.get(frame)?;
+        let mut world: Vec<(na::Rotation3<f32>, Vec3)> = Vec::with_capacity(self.joints.len());
+        for joint in &self.joints {
+            let mut translation = joint.offset;
+            let mut rotation = na::Rotation3::identity();
+            for (i, &channel) in joint.channels.iter().enumerate() {
+                let value = values[joint.first_channel + i];
+                match channel {
+                    Channel::Position(axis) => translation[axis] += value,
+                    Channel::Rotation(axis) => {
+                        let axis = match axis {
+                            0 => Vec3::x_axis(),
+                            1 => Vec3::y_axis(),
+                            _ => Vec3::z_axis(),
+                        };
+                        rotation *= na::Rotation3::from_axis_angle(&axis, value.to_radians());
+                    }
+                }
+            }
+            // Parents always come first
+            let pose = match joint.parent {
+                Some(p) => {
+                    let (parent_rotation, parent_position) = world[p];
+                    (
+                        parent_rotation * rotation,
+                        parent_position + parent_rotation * translation,
+                    )
+                }
+                None => (rotation, translation),
+            };
+            world.push(pose);
+        }
+        Some(
+            self.joints
+                .iter()
+                .zip(world)
+                .map(|(joint, (_, position))| (joint.name.clone(), position))
+                .collect(),
+        )
+    }
+
+    /// A template from this frame seen through the camera.
+    /// `names` pairs Nuitrack joints with BVH joint names.
+    /// Returns None if the frame doesn't exist
+    pub fn template(
+        &self,
+        frame: usize,
+        camera: &Camera,
+        names: &[(JointType, &str)],
+    ) -> Option<JointPos> {
+        let world = self.world_positions(frame)?;
+        let positions: HashMap<&str, &Vec3> = world
+            .iter()
+            .map(|(name, position)| (unqualified(name), position))
+            .collect();
+        let mut joints: Vec<Joint> = names
+            .iter()
+            .filter_map(|&(joint_type, name)| {
+                let position = camera.project(positions.get(name)?)?;
+                Some(Joint::new(joint_type, position))
+            })
+            .collect();
+
+        add_hands(&mut joints);
+
+        Some(
+            joints
+                .into_iter()
+                .map(|j| (j.joint_type, glm::vec2(j.proj.x, j.proj.y)))
+                .collect(),
+        )
+    }
+}
+
+/// Name without a namespace like `mixamorig:`
+fn unqualified(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+impl Camera {
+    /// A camera in front of these points looking back at their
+    /// middle with y up, far enough away to see all of them.
+    /// BVH skeletons face along +z
+    pub fn framing<'a, I>(points: I, aspect: f32) -> Self
+    where
+        I: IntoIterator<Item = &'a Vec3>,
+    {
+        let points: Vec<&Vec3> = points.into_iter().collect();
+        let count = points.len().max(1) as f32;
+        let target = points.iter().fold(glm::vec3(0.0, 0.0, 0.0), |c, &&p| c + p) / count;
+        let radius = points
+            .iter()
+            .map(|&&p| glm::distance(&p, &target))
+            .fold(f32::EPSILON, f32::max);
+        let fov = std::f32::consts::FRAC_PI_3;
+        // Leave a margin around the edge
+        let distance = radius * 1.2 / (fov / 2.0).tan() / aspect.min(1.0) + radius;
+        Camera {
+            position: target + glm::vec3(0.0, 0.0, distance),
+            target,
+            up: glm::vec3(0.0, 1.0, 0.0),
+            fov,
+            aspect,
+        }
+    }
+
+    /// Where a point lands in the image from 0 to 1 with y down.
+    /// Returns None if it's behind the camera
+    pub fn project(&self, point: &Vec3) -> Option<glm::Vec2> {
+        let forward = glm::normalize(&(self.target - self.position));
+        let right = glm::normalize(&forward.cross(&self.up));
+        let up = right.cross(&forward);
+
+        let d = point - self.position;
+        let depth = glm::dot(&d, &forward);
+        if depth <= 0.0 {
+            return None;
+        }
+        let half_height = (self.fov / 2.0).tan() * depth;
+        let half_width = half_height * self.aspect;
+        let x = glm::dot(&d, &right) / half_width;
+        let y = glm::dot(&d, &up) / half_height;
+        Some(glm::vec2((x + 1.0) / 2.0, (1.0 - y) / 2.0))
+    }
+}
+
+struct Parser<'a> {
+    /// Tokens with their line numbers
+    tokens: Vec<(usize, &'a str)>,
+    next: usize,
+    joints: Vec<BvhJoint>,
+    channels: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn token(&mut self) -> io::Result<(usize, &'a str)> {
+        let token = self
+            .tokens
+            .get(self.next)
+            .cloned()
+            .ok_or_else(|| invalid_data("unexpected end of file"))?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> io::Result<()> {
+        match self.token()? {
+            (_, t) if t == expected => Ok(()),
+            (line, t) => Err(invalid_data(format!(
+                "line {}: expected {} found {}",
+                line, expected, t
+            ))),
+        }
+    }
+
+    fn number(&mut self) -> io::Result<f32> {
+        let (line, t) = self.token()?;
+        t.parse()
+            .map_err(|_| invalid_data(format!("line {}: expected a number found {}", line, t)))
+    }
+
+    fn offset(&mut self) -> io::Result<Vec3> {
+        self.expect("OFFSET")?;
+        Ok(glm::vec3(self.number()?, self.number()?, self.number()?))
+    }
+
+    /// Parse a joint after `ROOT` or `JOINT` and its children
+    fn joint(&mut self, parent: Option<usize>) -> io::Result<()> {
+        let (_, name) = self.token()?;
+        self.expect("{")?;
+        let offset = self.offset()?;
+        let mut channels = Vec::new();
+        if self.tokens.get(self.next).map(|&(_, t)| t) == Some("CHANNELS") {
+            self.next += 1;
+            let count = self.number()? as usize;
+            for _ in 0..count {
+                let (line, t) = self.token()?;
+                let axis = match t.chars().next() {
+                    Some('X') => 0,
+                    Some('Y') => 1,
+                    Some('Z') => 2,
+                    _ => return Err(unknown_channel(line, t)),
+                };
+                channels.push(match &t[1..] {
+                    "position" => Channel::Position(axis),
+                    "rotation" => Channel::Rotation(axis),
+                    _ => return Err(unknown_channel(line, t)),
+                });
+            }
+        }
+        let index = self.joints.len();
+        self.joints.push(BvhJoint {
+            name: name.to_string(),
+            parent,
+            offset,
+            first_channel: self.channels,
+            channels,
+        });
+        self.channels += self.joints[index].channels.len();
+
+        loop {
+            match self.token()? {
+                (_, "JOINT") => self.joint(Some(index))?,
+                (_, "End") => {
+                    self.expect("Site")?;
+                    self.expect("{")?;
+                    let offset = self.offset()?;
+                    self.expect("}")?;
+                    self.joints.push(BvhJoint {
+                        name: format!("{}End", name),
+                        parent: Some(index),
+                        offset,
+                        channels: Vec::new(),
+                        first_channel: self.channels,
+                    });
+                }
+                (_, "}") => return Ok(()),
+                (line, t) => {
+                    return Err(invalid_data(format!(
+                        "line {}: expected JOINT, End or }} found {}",
+                        line, t
+                    )))
+                }
+            }
+        }
+    }
+}
+
+fn unknown_channel(line: usize, channel: &str) -> io::Error {
+    invalid_data(format!("line {}: unknown channel {}", line, channel))
+}
@@ -0,0 +1,47 @@
+use crate::{Failure, Implausible, JointType, Pose, Resolution};
+use std::fmt;
+
+/// Something wrong with a detector's settings or poses,
+/// or a skeleton that couldn't be checked at all.
+/// A skeleton that was checked and didn't match isn't an error
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The pose isn't one of the detector's poses
+    UnknownPose(Pose),
+    /// There are no poses to detect
+    EmptyLibrary,
+    /// A template is missing one of the `ARMS` joints
+    IncompleteTemplate(Pose, JointType),
+    /// A library's resolution has no pixels
+    InvalidResolution(Resolution),
+    /// A setting is out of range, with what's wrong
+    InvalidSettings(String),
+    /// The skeleton couldn't be compared to the template
+    NotEvaluated(Failure),
+    /// The skeleton was rejected before checking any pose
+    Implausible(Implausible),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnknownPose(pose) => write!(f, "Pose {} isn't loaded", pose.name()),
+            Error::EmptyLibrary => write!(f, "The pose library is empty"),
+            Error::IncompleteTemplate(pose, joint) => {
+                write!(f, "Template {} has no {}", pose.name(), joint.name())
+            }
+            Error::InvalidResolution(r) => {
+                write!(
+                    f,
+                    "The library resolution can't be {}x{}",
+                    r.width, r.height
+                )
+            }
+            Error::InvalidSettings(reason) => write!(f, "Invalid settings: {}", reason),
+            Error::NotEvaluated(failure) => write!(f, "Couldn't evaluate the pose: {:?}", failure),
+            Error::Implausible(reason) => write!(f, "Implausible skeleton: {:?}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,242 @@
+//! Measure how well a `Detector` does on labelled skeletons.
+//!
+//! With the `json` feature labelled frames can be read from JSON Lines,
+//! one frame per line:
+//!
+//! ```json
+//! {"label": "DabR", "joints": [{"type": "LeftShoulder", "proj": [0.68, 0.49, 0.0]}, ...]}
+//! ```
+//!
+//! A label of `"none"` means no pose should be detected.
+
+use crate::{Detector, Joint, Pose};
+use std::fmt;
+
+/// A skeleton and the pose it's meant to be
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frame {
+    /// `None` if no pose should be detected
+    #[cfg_attr(feature = "json", serde(with = "json::label"))]
+    pub label: Option<Pose>,
+    pub joints: Vec<Joint>,
+}
+
+/// Results of running a detector over labelled frames
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Evaluation {
+    /// Row and column labels of `confusion`.
+    /// `None` is no pose
+    pub classes: Vec<Option<Pose>>,
+    /// Frame counts indexed by `[label][detected]`
+    pub confusion: Vec<Vec<usize>>,
+    pub metrics: Vec<PoseMetrics>,
+    pub frames: usize,
+    /// Frames labelled with no pose
+    pub none_frames: usize,
+    /// Fraction of `none_frames` where a pose was detected
+    pub false_positive_rate: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct PoseMetrics {
+    pub pose: Pose,
+    pub precision: f32,
+    pub recall: f32,
+    pub f1: f32,
+    /// Frames labelled with this pose
+    pub support: usize,
+}
+
+/// Run the detector over every frame and compare to the labels
+pub fn evaluate(detector: &Detector, frames: &[Frame]) -> Evaluation {
+    Evaluation::from_detections(
+        frames
+            .iter()
+            .map(|frame| (frame.label, detector.detect(&frame.joints))),
+    )
+}
+
+impl Evaluation {
+    /// Build an evaluation from `(label, detected)` pairs
+    pub fn from_detections<I>(detections: I) -> Self
+    where
+        I: IntoIterator<Item = (Option<Pose>, Option<Pose>)>,
+    {
+        let classes: Vec<Option<Pose>> = Pose::ALL
+            .iter()
+            .cloned()
+            .map(Some)
+            .chain(Some(None))
+            .collect();
+        let index = |pose: Option<Pose>| classes.iter().position(|&c| c == pose).unwrap();
+
+        let mut confusion = vec![vec![0; classes.len()]; classes.len()];
+        let mut frames = 0;
+        for (label, detected) in detections {
+            confusion[index(label)][index(detected)] += 1;
+            frames += 1;
+        }
+
+        let metrics = Pose::ALL
+            .iter()
+            .map(|&pose| {
+                let i = index(Some(pose));
+                let tp = confusion[i][i];
+                let detected: usize = confusion.iter().map(|row| row[i]).sum();
+                let support: usize = confusion[i].iter().sum();
+                let precision = ratio(tp, detected);
+                let recall = ratio(tp, support);
+                let f1 = if precision + recall > 0.0 {
+                    2.0 * precision * recall / (precision + recall)
+                } else {
+                    0.0
+                };
+                PoseMetrics {
+                    pose,
+                    precision,
+                    recall,
+                    f1,
+                    support,
+                }
+            })
+            .collect();
+
+        let none = index(None);
+        let none_frames: usize = confusion[none].iter().sum();
+        let false_positive_rate = ratio(none_frames - confusion[none][none], none_frames);
+
+        Evaluation {
+            classes,
+            confusion,
+            metrics,
+            frames,
+            none_frames,
+            false_positive_rate,
+        }
+    }
+
+    /// Mean F1 over the poses that had labelled frames
+    pub fn macro_f1(&self) -> f32 {
+        let labelled: Vec<&PoseMetrics> = self.metrics.iter().filter(|m| m.support > 0).collect();
+        if labelled.is_empty() {
+            0.0
+        } else {
+            labelled.iter().map(|m| m.f1).sum::<f32>() / labelled.len() as f32
+        }
+    }
+}
+
+fn ratio(n: usize, d: usize) -> f32 {
+    if d == 0 {
+        0.0
+    } else {
+        n as f32 / d as f32
+    }
+}
+
+fn class_name(class: Option<Pose>) -> &'static str {
+    class.map(|p| p.name()).unwrap_or("none")
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Confusion matrix (rows labelled, columns detected)")?;
+        write!(f, "{:>10}", "")?;
+        for &class in &self.classes {
+            write!(f, "{:>9}", class_name(class))?;
+        }
+        writeln!(f)?;
+        for (&class, row) in self.classes.iter().zip(self.confusion.iter()) {
+            write!(f, "{:>10}", class_name(class))?;
+            for count in row {
+                write!(f, "{:>9}", count)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>10}{:>11}{:>9}{:>9}{:>9}",
+            "pose", "precision", "recall", "f1", "frames"
+        )?;
+        for m in &self.metrics {
+            writeln!(
+                f,
+                "{:>10}{:>11.3}{:>9.3}{:>9.3}{:>9}",
+                m.pose.name(),
+                m.precision,
+                m.recall,
+                m.f1,
+                m.support
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Frames: {}", self.frames)?;
+        writeln!(
+            f,
+            "False positive rate on none: {:.3} ({} frames)",
+            self.false_positive_rate, self.none_frames
+        )
+    }
+}
+
+#[cfg(feature = "json")]
+pub use self::json::{load, read};
+
+#[cfg(feature = "json")]
+mod json {
+    use super::Frame;
+    use crate::invalid_data;
+    use std::fs::File;
+    use std::io::{self, BufRead, BufReader};
+    use std::path::Path;
+
+    /// Load labelled frames from a JSON Lines file
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Vec<Frame>> {
+        read(BufReader::new(File::open(path)?))
+    }
+
+    /// Read labelled frames from JSON Lines
+    pub fn read<R: BufRead>(reader: R) -> io::Result<Vec<Frame>> {
+        let mut frames = Vec::new();
+        for (n, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let frame = serde_json::from_str(&line)
+                .map_err(|e| invalid_data(format!("line {}: {}", n + 1, e)))?;
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+
+    /// Labels are a pose name or `"none"`
+    pub mod label {
+        use crate::Pose;
+        use serde::de::Error;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            label: &Option<Pose>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(label.map(|p| p.name()).unwrap_or("none"))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Pose>, D::Error> {
+            let name = String::deserialize(deserializer)?;
+            match name.as_str() {
+                "none" => Ok(None),
+                name => Pose::from_name(name)
+                    .map(Some)
+                    .ok_or_else(|| D::Error::custom(format!("unknown pose {}", name))),
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! C interface for embedding the detector in other languages.
+//! The header is `include/nuitrack_pose_estimation.h`, which is
+//! generated from this module with cbindgen.
+//!
+//! Ownership:
+//! - A detector made with `npe_detector_new` is owned by the caller
+//!   and must be freed once with `npe_detector_free`.
+//! - Strings and arrays passed in are only borrowed for the call.
+//! - Strings returned by `npe_pose_name` are static and
+//!   the one from `npe_last_error` is owned by the library.
+//!
+//! Functions that can fail return an `NPE_*` status and
+//! write their results through out pointers.
+//! Pointers must be null or valid for the call, which
+//! covers the safety requirements of every function here.
+
+#![allow(clippy::missing_safety_doc)]
+
+use crate::{library, Detector, JointInput, JointType, Pose, Vec2};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+pub const NPE_OK: i32 = 0;
+/// A required pointer was null
+pub const NPE_NULL_POINTER: i32 = -1;
+/// An argument was out of range or not valid UTF-8
+pub const NPE_INVALID_ARGUMENT: i32 = -2;
+/// A pose library couldn't be read or parsed
+pub const NPE_LIBRARY_ERROR: i32 = -3;
+/// Something went wrong inside the library
+pub const NPE_PANIC: i32 = -4;
+
+/// Written to `pose_out` when no pose was detected
+pub const NPE_NO_POSE: i32 = -1;
+
+/// Poses, as written to `pose_out`
+pub const NPE_POSE_DAB_R: i32 = 0;
+pub const NPE_POSE_DAB_L: i32 = 1;
+pub const NPE_POSE_HANDS_UP: i32 = 2;
+pub const NPE_POSE_ROOF: i32 = 3;
+pub const NPE_POSE_FLYING_R: i32 = 4;
+pub const NPE_POSE_FLYING_L: i32 = 5;
+
+/// Joint types, the same as Nuitrack's
+pub const NPE_JOINT_HEAD: u32 = 1;
+pub const NPE_JOINT_NECK: u32 = 2;
+pub const NPE_JOINT_TORSO: u32 = 3;
+pub const NPE_JOINT_WAIST: u32 = 4;
+pub const NPE_JOINT_LEFT_COLLAR: u32 = 5;
+pub const NPE_JOINT_LEFT_SHOULDER: u32 = 6;
+pub const NPE_JOINT_LEFT_ELBOW: u32 = 7;
+pub const NPE_JOINT_LEFT_WRIST: u32 = 8;
+pub const NPE_JOINT_LEFT_HAND: u32 = 9;
+pub const NPE_JOINT_LEFT_FINGERTIP: u32 = 10;
+pub const NPE_JOINT_RIGHT_COLLAR: u32 = 11;
+pub const NPE_JOINT_RIGHT_SHOULDER: u32 = 12;
+pub const NPE_JOINT_RIGHT_ELBOW: u32 = 13;
+pub const NPE_JOINT_RIGHT_WRIST: u32 = 14;
+pub const NPE_JOINT_RIGHT_HAND: u32 = 15;
+pub const NPE_JOINT_RIGHT_FINGERTIP: u32 = 16;
+pub const NPE_JOINT_LEFT_HIP: u32 = 17;
+pub const NPE_JOINT_LEFT_KNEE: u32 = 18;
+pub const NPE_JOINT_LEFT_ANKLE: u32 = 19;
+pub const NPE_JOINT_LEFT_FOOT: u32 = 20;
+pub const NPE_JOINT_RIGHT_HIP: u32 = 21;
+pub const NPE_JOINT_RIGHT_KNEE: u32 = 22;
+pub const NPE_JOINT_RIGHT_ANKLE: u32 = 23;
+pub const NPE_JOINT_RIGHT_FOOT: u32 = 24;
+
+/// A joint in image coordinates.
+/// `joint_type` uses Nuitrack's joint ids
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FfiJoint {
+    pub joint_type: u32,
+    pub x: f32,
+    pub y: f32,
+    pub confidence: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FfiSettings {
+    pub joint_cutoff: f32,
+    pub rotation_cutoff: f32,
+}
+
+const POSE_NAMES: [&[u8]; 6] = [
+    b"DabR\0",
+    b"DabL\0",
+    b"HandsUp\0",
+    b"Roof\0",
+    b"FlyingR\0",
+    b"FlyingL\0",
+];
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+impl JointInput for FfiJoint {
+    fn joint_type(&self) -> Option<JointType> {
+        JointType::from_u32(self.joint_type)
+    }
+
+    fn position(&self) -> Vec2 {
+        crate::glm::vec2(self.x, self.y)
+    }
+}
+
+/// Make a detector with the default settings and poses.
+/// Free it with `npe_detector_free`. Returns null on failure
+#[no_mangle]
+pub extern "C" fn npe_detector_new() -> *mut Detector {
+    panic::catch_unwind(|| Box::into_raw(Box::new(Detector::default()))).unwrap_or(ptr::null_mut())
+}
+
+/// Free a detector. Null is ignored
+#[no_mangle]
+pub unsafe extern "C" fn npe_detector_free(detector: *mut Detector) {
+    if !detector.is_null() {
+        drop(Box::from_raw(detector));
+    }
+}
+
+/// Replace the poses with a library read from a JSON file
+#[no_mangle]
+pub unsafe extern "C" fn npe_detector_load_poses_file(
+    detector: *const Detector,
+    path: *const c_char,
+) -> i32 {
+    guard(|| {
+        let detector = detector.as_ref().ok_or(NPE_NULL_POINTER)?;
+        let path = string(path)?;
+        let library = library::load(path).map_err(|e| fail(NPE_LIBRARY_ERROR, e))?;
+        library.validate().map_err(|e| fail(NPE_LIBRARY_ERROR, e))?;
+        detector.set_library(library);
+        Ok(())
+    })
+}
+
+/// Replace the poses with a library from a JSON string
+#[no_mangle]
+pub unsafe extern "C" fn npe_detector_load_poses_str(
+    detector: *const Detector,
+    json: *const c_char,
+) -> i32 {
+    guard(|| {
+        let detector = detector.as_ref().ok_or(NPE_NULL_POINTER)?;
+        let library = library::from_str(string(json)?).map_err(|e| fail(NPE_LIBRARY_ERROR, e))?;
+        library.validate().map_err(|e| fail(NPE_LIBRARY_ERROR, e))?;
+        detector.set_library(library);
+        Ok(())
+    })
+}
+
+/// Set the cutoffs. They must be positive
+#[no_mangle]
+pub unsafe extern "C" fn npe_detector_set_settings(
+    detector: *const Detector,
+    settings: *const FfiSettings,
+) -> i32 {
+    guard(|| {
+        let detector = detector.as_ref().ok_or(NPE_NULL_POINTER)?;
+        let settings = settings.as_ref().ok_or(NPE_NULL_POINTER)?;
+        let mut new = detector.settings();
+        new.joint_cutoff = settings.joint_cutoff;
+        new.rotation_cutoff = settings.rotation_cutoff;
+        new.validate().map_err(|e| fail(NPE_INVALID_ARGUMENT, e))?;
+        detector.set_settings(new);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn npe_detector_get_settings(
+    detector: *const Detector,
+    settings_out: *mut FfiSettings,
+) -> i32 {
+    guard(|| {
+        let detector = detector.as_ref().ok_or(NPE_NULL_POINTER)?;
+        let out = settings_out.as_mut().ok_or(NPE_NULL_POINTER)?;
+        let settings = detector.settings();
+        *out = FfiSettings {
+            joint_cutoff: settings.joint_cutoff,
+            rotation_cutoff: settings.rotation_cutoff,
+        };
+        Ok(())
+    })
+}
+
+/// Detect a pose in `count` joints.
+/// Writes the pose or `NPE_NO_POSE` to `pose_out`.
+/// `score_out` may be null, otherwise it gets the score of the
+/// closest pose from 0 to 1, see `PoseReport::score`
+#[no_mangle]
+pub unsafe extern "C" fn npe_detector_detect(
+    detector: *const Detector,
+    joints: *const FfiJoint,
+    count: usize,
+    pose_out: *mut i32,
+    score_out: *mut f32,
+) -> i32 {
+    guard(|| {
+        let detector = detector.as_ref().ok_or(NPE_NULL_POINTER)?;
+        let pose_out = pose_out.as_mut().ok_or(NPE_NULL_POINTER)?;
+        if joints.is_null() && count > 0 {
+            return Err(NPE_NULL_POINTER);
+        }
+        let joints = if count == 0 {
+            &[]
+        } else {
+            std::slice::from_raw_parts(joints, count)
+        };
+        let report = detector.detect_explain(joints);
+        *pose_out = report.pose.map(pose_id).unwrap_or(NPE_NO_POSE);
+        if let Some(score_out) = score_out.as_mut() {
+            *score_out = report.poses.first().map(|p| p.score()).unwrap_or(0.0);
+        }
+        Ok(())
+    })
+}
+
+/// Name of a pose, or null if there's no such pose.
+/// The string is static and mustn't be freed
+#[no_mangle]
+pub extern "C" fn npe_pose_name(pose: i32) -> *const c_char {
+    if pose < 0 {
+        return ptr::null();
+    }
+    POSE_NAMES
+        .get(pose as usize)
+        .map(|name| name.as_ptr() as *const c_char)
+        .unwrap_or(ptr::null())
+}
+
+/// Message for the last error on this thread, or null.
+/// Valid until the next call on this thread
+#[no_mangle]
+pub extern "C" fn npe_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| {
+        e.borrow()
+            .as_ref()
+            .map(|s| s.as_ptr())
+            .unwrap_or(ptr::null())
+    })
+}
+
+fn pose_id(pose: Pose) -> i32 {
+    Pose::ALL.iter().position(|&p| p == pose).unwrap_or(0) as i32
+}
+
+/// Run a call, turning errors and panics into status codes
+fn guard<F>(f: F) -> i32
+where
+    F: FnOnce() -> Result<(), i32>,
+{
+    LAST_ERROR.with(|e| *e.borrow_mut() = None);
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => NPE_OK,
+        Ok(Err(status)) => status,
+        Err(_) => fail(NPE_PANIC, "Panicked"),
+    }
+}
+
+/// Remember the message and return the status
+fn fail<E: ToString>(status: i32, error: E) -> i32 {
+    let message = CString::new(error.to_string()).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+    status
+}
+
+unsafe fn string<'a>(s: *const c_char) -> Result<&'a str, i32> {
+    if s.is_null() {
+        return Err(NPE_NULL_POINTER);
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|e| fail(NPE_INVALID_ARGUMENT, e))
+}
@@ -0,0 +1,49 @@
+use crate::{glm, JointType, PoseReport, Vec2, ARMS};
+use std::cmp::Ordering::Equal;
+
+/// Where a joint is and where the template wants it,
+/// both in image coordinates
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Correction {
+    pub joint: JointType,
+    pub position: Vec2,
+    pub target: Vec2,
+    /// Move from `position` to `target`
+    pub displacement: Vec2,
+}
+
+impl Correction {
+    /// How far the joint needs to move
+    pub fn distance(&self) -> f32 {
+        glm::length(&self.displacement)
+    }
+}
+
+impl PoseReport {
+    /// Map the aligned template back into image coordinates
+    /// and work out how each joint needs to move to reach it,
+    /// largest move first.
+    /// Returns None if the template couldn't be aligned
+    pub fn corrections(&self) -> Option<Vec<Correction>> {
+        if self.skeleton.len() != ARMS.len() || self.template.len() != ARMS.len() {
+            return None;
+        }
+        let mut corrections = ARMS
+            .iter()
+            .zip(self.skeleton.iter().zip(self.template.iter()))
+            .map(|(&joint, (s, t))| {
+                let position = self.to_image(s)?;
+                let target = self.to_image(t)?;
+                Some(Correction {
+                    joint,
+                    position,
+                    target,
+                    displacement: target - position,
+                })
+            })
+            .collect::<Option<Vec<Correction>>>()?;
+        corrections.sort_by(|a, b| b.distance().partial_cmp(&a.distance()).unwrap_or(Equal));
+        Some(corrections)
+    }
+}
@@ -1,20 +1,51 @@
 use nalgebra as na;
 use nalgebra_glm as glm;
+#[cfg(feature = "nuitrack")]
 use nuitrack_rs as nui;
 
+pub mod bvh;
+mod error;
+pub mod evaluate;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod guidance;
+#[cfg(feature = "json")]
+pub mod library;
+pub mod mediapipe;
+#[cfg(feature = "nuitrack")]
+mod nuitrack;
+pub mod openpose;
+pub mod osc;
 mod poses;
+#[cfg(feature = "json")]
+mod record;
+#[cfg(feature = "json")]
+pub mod recording;
+#[cfg(feature = "json")]
+pub mod reload;
+mod skeleton;
+pub mod svg;
+pub mod synth;
+pub mod tracker;
+pub mod tune;
+#[cfg(feature = "websocket")]
+pub mod websocket;
 
-use self::nui::{Joint, JointType};
-use glm::{Mat2x2, Vec2};
+pub use self::error::Error;
+pub use self::guidance::Correction;
+pub use self::skeleton::{Joint, JointInput, JointType, Skeleton};
+use glm::{Mat2x2, Vec2, Vec3};
 use na::MatrixMN;
-use std::cell::RefCell;
 use std::cmp::Ordering::Equal;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io;
 use std::iter::FromIterator;
+use std::sync::{Arc, RwLock, RwLockWriteGuard};
 
 type Mat2x8 = MatrixMN<f32, na::U2, na::U8>;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub enum Pose {
     DabR,
     DabL,
@@ -28,24 +59,242 @@ pub type PoseData = HashMap<Pose, JointPos>;
 
 pub type JointPos = HashMap<JointType, Vec2>;
 
+/// Templates and the size of the frames they were captured at
+#[derive(Clone, Debug, PartialEq)]
+pub struct Library {
+    pub poses: PoseData,
+    /// None means the same size as the skeletons
+    pub resolution: Option<Resolution>,
+}
+
+/// Detects poses in skeletons.
+/// Can be shared between threads and have its
+/// settings and poses swapped while running.
 pub struct Detector {
-    pub settings: Settings,
-    poses: PoseData,
-    pub lasts: Lasts,
+    config: RwLock<Config>,
+}
+
+#[derive(Clone)]
+struct Config {
+    settings: Arc<Settings>,
+    poses: Arc<PoseData>,
+    template_resolution: Option<Resolution>,
+}
+
+/// Everything the detector found when checking a skeleton
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report {
+    /// The closest matching pose, if any matched
+    pub pose: Option<Pose>,
+    /// One report per template, matches first and closest first.
+    /// Empty if the skeleton was rejected
+    pub poses: Vec<PoseReport>,
+    /// Why the skeleton was rejected before checking any template
+    #[cfg_attr(feature = "json", serde(default))]
+    pub rejected: Option<Implausible>,
+}
+
+/// How a single template compared to the skeleton
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct PoseReport {
+    pub pose: Pose,
+    /// Skeleton arms after centering, in `ARMS` order
+    pub skeleton: Vec<Vec2>,
+    /// Template arms after scaling, centering and rotating onto the skeleton
+    pub template: Vec<Vec2>,
+    /// Distance of each aligned joint from the template, in `ARMS` order.
+    /// In shoulder widths with `Normalisation::Torso`
+    pub joints: Vec<JointDistance>,
+    /// Radians the skeleton is turned from the template,
+    /// clockwise in the image when positive
+    pub rotation: Option<f32>,
+    pub scale: Option<f32>,
+    /// Centre of the skeleton arms, see `to_image`
+    pub centre: Option<Vec2>,
+    /// Which way round the template was compared
+    #[cfg_attr(feature = "json", serde(default))]
+    pub chirality: Chirality,
+    /// Width over height of the skeleton's frame.
+    /// Everything above has x multiplied by this so
+    /// distances and rotations are the same in every direction
+    pub aspect: f32,
+    /// Why this pose didn't match
+    pub failure: Option<Failure>,
 }
 
-#[derive(Default)]
-pub struct Lasts {
-    pub capture: bool,
-    pub skeleton: RefCell<Option<Vec<Vec2>>>,
-    pub pose: RefCell<Option<Vec<Vec2>>>,
-    pub max_dist: RefCell<Option<f32>>,
-    pub rotations: RefCell<Option<f32>>,
+/// Which way round a template matched
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum Chirality {
+    /// As the template is stored
+    #[default]
+    Same,
+    /// The template's mirror image, with left and right swapped
+    Mirrored,
 }
 
+/// How far a joint was from where the template wanted it
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct JointDistance {
+    pub joint: JointType,
+    pub distance: f32,
+    /// The `joint_cutoff` this was checked against
+    pub cutoff: f32,
+}
+
+/// Reason a template didn't match
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum Failure {
+    /// This arm joint wasn't in the skeleton
+    MissingJoint(JointType),
+    /// This arm joint wasn't in the template
+    IncompleteTemplate(JointType),
+    /// The template or skeleton arms have no length
+    DegenerateScale,
+    /// The rotation needed to align the template was
+    /// outside what the pose's `Rotation` allows
+    RotationExceeded(f32),
+    /// The furthest joint from the template was over `joint_cutoff`
+    JointTooFar(JointType, f32),
+    /// The shoulders were tilted from level by more than `max_tilt`
+    TorsoTilted(f32),
+}
+
+/// Why a skeleton was rejected before any template was checked
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum Implausible {
+    /// The left shoulder wasn't on the right of the image
+    /// from the right shoulder, see `Plausibility::sides`
+    SidesSwapped,
+    /// The hands were much closer to the other arm's wrists than their own
+    HandsSwapped,
+    /// The bone ending at this joint was outside its `Plausibility` bounds,
+    /// with its length in millimetres.
+    /// The shoulder width ends at `LeftShoulder`
+    BoneLength(JointType, f32),
+    /// Forearm over upper arm length was outside `forearm_ratio`,
+    /// with the arm's wrist and the ratio
+    BoneRatio(JointType, f32),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct Settings {
     pub joint_cutoff: f32,
     pub rotation_cutoff: f32,
+    /// Cutoffs for poses that need different ones
+    #[cfg_attr(feature = "json", serde(default))]
+    pub overrides: HashMap<Pose, Cutoffs>,
+    /// Size of the frames skeletons come from.
+    /// None compares them to the templates as they are
+    #[cfg_attr(feature = "json", serde(default))]
+    pub resolution: Option<Resolution>,
+    /// How templates are lined up with skeletons
+    #[cfg_attr(feature = "json", serde(default))]
+    pub normalisation: Normalisation,
+    /// Radians the shoulders can tilt from level before nothing matches.
+    /// None doesn't check
+    #[cfg_attr(feature = "json", serde(default))]
+    pub max_tilt: Option<f32>,
+    /// Mirror skeletons before detecting, for displays that show a
+    /// mirrored camera. Poses, joints and positions in reports are
+    /// then as the user sees them on the display
+    #[cfg_attr(feature = "json", serde(default))]
+    pub mirror: bool,
+    /// Poses that also match their mirror image, so one
+    /// template covers both the left and right handed pose
+    #[cfg_attr(feature = "json", serde(default))]
+    pub either_hand: HashSet<Pose>,
+    /// Which rotations of the skeleton from a template can match
+    #[cfg_attr(feature = "json", serde(default))]
+    pub rotation: Rotation,
+    /// Rotations for poses that need different ones
+    #[cfg_attr(feature = "json", serde(default))]
+    pub rotation_overrides: HashMap<Pose, Rotation>,
+    /// Reject skeletons whose arms can't be a person before
+    /// comparing them to any template. None doesn't check
+    #[cfg_attr(feature = "json", serde(default))]
+    pub plausibility: Option<Plausibility>,
+}
+
+/// What arms a person can have, for rejecting broken skeletons.
+/// Lengths are measured between the tracker's 3D positions, see
+/// `JointInput::real`, and aren't checked for trackers without them
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plausibility {
+    /// Shoulder to shoulder in millimetres
+    pub shoulders: Bounds,
+    /// Shoulder to elbow in millimetres
+    pub upper_arm: Bounds,
+    /// Elbow to wrist in millimetres
+    pub forearm: Bounds,
+    /// Wrist to hand in millimetres
+    pub hand: Bounds,
+    /// Forearm length over upper arm length
+    pub forearm_ratio: Bounds,
+    /// Check the left shoulder is on the right of the image from the
+    /// right shoulder, as when facing the camera. Turn off for people
+    /// who face away or lie down
+    pub sides: bool,
+}
+
+/// An inclusive range
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bounds {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Which rotations of the skeleton from a template can match.
+/// Angles are in radians
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rotation {
+    /// Up to the pose's `rotation_cutoff` either way
+    #[default]
+    Upright,
+    /// Any rotation, so only the shape has to match.
+    /// For people lying down or sensors on the ceiling
+    Free,
+    /// Up to `clockwise` one way and `anticlockwise` the other,
+    /// as seen in the image. For poses done leaning one way
+    Range { clockwise: f32, anticlockwise: f32 },
+}
+
+/// How templates are lined up with skeletons before comparing
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum Normalisation {
+    /// Scale, centre and rotate the template to best fit all the arm joints
+    #[default]
+    Arms,
+    /// Put both in a frame on the torso, with the origin between the
+    /// shoulders, x along the shoulders and shoulder width as the unit.
+    /// `joint_cutoff` is then in shoulder widths
+    Torso,
+}
+
+/// Size of a frame in pixels
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Cutoffs for a single pose
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cutoffs {
+    pub joint_cutoff: f32,
+    pub rotation_cutoff: f32,
 }
 
 pub struct Tester {
@@ -54,6 +303,27 @@ pub struct Tester {
 }
 
 impl Pose {
+    /// Every pose in a fixed order
+    pub const ALL: [Pose; 6] = [
+        Pose::DabR,
+        Pose::DabL,
+        Pose::HandsUp,
+        Pose::Roof,
+        Pose::FlyingR,
+        Pose::FlyingL,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pose::DabR => "DabR",
+            Pose::DabL => "DabL",
+            Pose::HandsUp => "HandsUp",
+            Pose::Roof => "Roof",
+            Pose::FlyingR => "FlyingR",
+            Pose::FlyingL => "FlyingL",
+        }
+    }
+
     pub fn from_name(name: &str) -> Option<Self> {
         match name {
             "DabR" => Some(Pose::DabR),
@@ -78,127 +348,778 @@ impl Default for Settings {
         Settings {
             joint_cutoff: 0.13,
             rotation_cutoff: 0.22,
+            overrides: HashMap::new(),
+            resolution: None,
+            normalisation: Normalisation::Arms,
+            max_tilt: None,
+            mirror: false,
+            either_hand: HashSet::new(),
+            rotation: Rotation::Upright,
+            rotation_overrides: HashMap::new(),
+            plausibility: None,
+        }
+    }
+}
+
+impl Default for Plausibility {
+    /// Wide enough for children and adults
+    fn default() -> Self {
+        Plausibility {
+            shoulders: Bounds::new(150.0, 600.0),
+            upper_arm: Bounds::new(150.0, 500.0),
+            forearm: Bounds::new(120.0, 450.0),
+            hand: Bounds::new(0.0, 250.0),
+            forearm_ratio: Bounds::new(0.5, 1.6),
+            sides: true,
+        }
+    }
+}
+
+impl Settings {
+    /// The cutoffs used for this pose
+    pub fn cutoffs(&self, pose: Pose) -> Cutoffs {
+        self.overrides.get(&pose).cloned().unwrap_or(Cutoffs {
+            joint_cutoff: self.joint_cutoff,
+            rotation_cutoff: self.rotation_cutoff,
+        })
+    }
+
+    /// The rotations allowed for this pose
+    pub fn rotation(&self, pose: Pose) -> Rotation {
+        self.rotation_overrides
+            .get(&pose)
+            .cloned()
+            .unwrap_or(self.rotation)
+    }
+
+    /// Would this pose match with this rotation and furthest joint
+    pub fn accepts(&self, pose: Pose, rotation: f32, furthest: f32) -> bool {
+        let cutoffs = self.cutoffs(pose);
+        self.rotation(pose)
+            .allows(rotation, cutoffs.rotation_cutoff)
+            && furthest < cutoffs.joint_cutoff
+    }
+
+    /// Check every cutoff is a positive number
+    pub fn validate(&self) -> Result<(), Error> {
+        let cutoffs = Cutoffs {
+            joint_cutoff: self.joint_cutoff,
+            rotation_cutoff: self.rotation_cutoff,
+        };
+        cutoffs.validate("")?;
+        for (pose, cutoffs) in &self.overrides {
+            cutoffs.validate(&format!(" for {}", pose.name()))?;
+        }
+        self.rotation.validate("")?;
+        for (pose, rotation) in &self.rotation_overrides {
+            rotation.validate(&format!(" for {}", pose.name()))?;
+        }
+        if let Some(plausibility) = self.plausibility {
+            plausibility.validate()?;
+        }
+        match self.max_tilt {
+            Some(tilt) if !(tilt > 0.0 && tilt.is_finite()) => {
+                return Err(Error::InvalidSettings(format!(
+                    "max_tilt must be a positive number, not {}",
+                    tilt
+                )));
+            }
+            _ => (),
+        }
+        match self.resolution {
+            Some(r) if r.is_empty() => Err(Error::InvalidSettings(format!(
+                "resolution can't be {}x{}",
+                r.width, r.height
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Library {
+    /// Check every template has the arm joints and
+    /// the resolution isn't empty
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_poses(&self.poses)?;
+        match self.resolution {
+            Some(r) if r.is_empty() => Err(Error::InvalidResolution(r)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for Library {
+    /// The built in templates
+    fn default() -> Self {
+        Library {
+            poses: poses::load(),
+            resolution: Some(Resolution::NUITRACK),
+        }
+    }
+}
+
+impl From<PoseData> for Library {
+    fn from(poses: PoseData) -> Self {
+        Library {
+            poses,
+            resolution: None,
         }
     }
 }
 
+impl Resolution {
+    /// Nuitrack's default depth resolution,
+    /// which the built in templates were captured at
+    pub const NUITRACK: Resolution = Resolution {
+        width: 640,
+        height: 480,
+    };
+
+    pub fn new(width: u32, height: u32) -> Self {
+        Resolution { width, height }
+    }
+
+    /// Width over height
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height as f32
+    }
+
+    fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+impl Cutoffs {
+    /// Would an upright pose with this rotation and furthest joint match,
+    /// see `Settings::accepts` for other rotations
+    pub fn accepts(&self, rotation: f32, furthest: f32) -> bool {
+        rotation.abs() <= self.rotation_cutoff && furthest < self.joint_cutoff
+    }
+
+    fn validate(&self, pose: &str) -> Result<(), Error> {
+        let cutoffs = [
+            ("joint_cutoff", self.joint_cutoff),
+            ("rotation_cutoff", self.rotation_cutoff),
+        ];
+        for &(name, cutoff) in cutoffs.iter() {
+            if !(cutoff > 0.0 && cutoff.is_finite()) {
+                return Err(Error::InvalidSettings(format!(
+                    "{}{} must be a positive number, not {}",
+                    name, pose, cutoff
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Plausibility {
+    /// Why these arms can't be a person, None if they can.
+    /// `image` is in `ARMS` order with the aspect corrected,
+    /// and `real` the same joints in 3D if the tracker has them
+    fn check(&self, image: &[Vec2], real: Option<&[Vec3]>) -> Option<Implausible> {
+        if self.sides && image[4].x <= image[0].x {
+            return Some(Implausible::SidesSwapped);
+        }
+        // Hands that touch are near both wrists, so they're only
+        // swapped if that's clearly the better fit
+        let distance = |a: usize, b: usize| glm::distance(&image[a], &image[b]);
+        let own = distance(3, 2) + distance(7, 6);
+        let swapped = distance(3, 6) + distance(7, 2);
+        if swapped + HANDS_SWAPPED_MARGIN * distance(0, 4) < own {
+            return Some(Implausible::HandsSwapped);
+        }
+
+        let real = real?;
+        let length = |a: usize, b: usize| glm::distance(&real[a], &real[b]);
+        let bones = [
+            (0, 4, self.shoulders),
+            (0, 1, self.upper_arm),
+            (1, 2, self.forearm),
+            (2, 3, self.hand),
+            (4, 5, self.upper_arm),
+            (5, 6, self.forearm),
+            (6, 7, self.hand),
+        ];
+        for &(a, b, bounds) in bones.iter() {
+            if !bounds.contains(length(a, b)) {
+                return Some(Implausible::BoneLength(ARMS[b], length(a, b)));
+            }
+        }
+        for &shoulder in [0, 4].iter() {
+            let ratio = length(shoulder + 1, shoulder + 2) / length(shoulder, shoulder + 1);
+            if !self.forearm_ratio.contains(ratio) {
+                return Some(Implausible::BoneRatio(ARMS[shoulder + 2], ratio));
+            }
+        }
+        None
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        let bounds = [
+            ("shoulders", self.shoulders),
+            ("upper_arm", self.upper_arm),
+            ("forearm", self.forearm),
+            ("hand", self.hand),
+            ("forearm_ratio", self.forearm_ratio),
+        ];
+        for &(name, b) in bounds.iter() {
+            if !(b.min >= 0.0 && b.min <= b.max && b.max.is_finite()) {
+                return Err(Error::InvalidSettings(format!(
+                    "plausibility {} must have 0 <= min <= max, not {} to {}",
+                    name, b.min, b.max
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Bounds {
+    pub fn new(min: f32, max: f32) -> Self {
+        Bounds { min, max }
+    }
+
+    /// False for NaN
+    pub fn contains(&self, value: f32) -> bool {
+        value >= self.min && value <= self.max
+    }
+}
+
+impl Rotation {
+    /// Is this rotation allowed, with `cutoff` the
+    /// pose's `rotation_cutoff` for `Upright`
+    pub fn allows(&self, rotation: f32, cutoff: f32) -> bool {
+        match *self {
+            Rotation::Upright => rotation.abs() <= cutoff,
+            Rotation::Free => true,
+            Rotation::Range {
+                clockwise,
+                anticlockwise,
+            } => rotation <= clockwise && -rotation <= anticlockwise,
+        }
+    }
+
+    fn validate(&self, pose: &str) -> Result<(), Error> {
+        if let Rotation::Range {
+            clockwise,
+            anticlockwise,
+        } = *self
+        {
+            let limits = [("clockwise", clockwise), ("anticlockwise", anticlockwise)];
+            for &(name, limit) in limits.iter() {
+                if !(0.0..=std::f32::consts::PI).contains(&limit) {
+                    return Err(Error::InvalidSettings(format!(
+                        "{} rotation{} must be from 0 to pi, not {}",
+                        name, pose, limit
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Detector {
     /// Create a new detector with settings
     /// Settings can be set to default
     /// This will use the default poses
     pub fn new(settings: Settings) -> Self {
-        Self::with_poses(settings, poses::load())
+        Self::with_library(settings, Library::default())
     }
 
     /// Create a detector with custom poses
+    /// captured at the same resolution as the skeletons
     pub fn with_poses(settings: Settings, poses: PoseData) -> Self {
-        let lasts = Lasts::default();
+        Self::with_library(settings, Library::from(poses))
+    }
+
+    /// Create a detector with a custom library
+    pub fn with_library(settings: Settings, library: Library) -> Self {
         Detector {
-            poses,
-            settings,
-            lasts,
+            config: RwLock::new(Config::new(settings, library)),
         }
     }
 
+    /// Create a detector with custom poses,
+    /// checking the settings and every template first
+    pub fn try_with_poses(settings: Settings, poses: PoseData) -> Result<Self, Error> {
+        Self::try_with_library(settings, Library::from(poses))
+    }
+
+    /// Create a detector with a custom library,
+    /// checking the settings and every template first
+    pub fn try_with_library(settings: Settings, library: Library) -> Result<Self, Error> {
+        settings.validate()?;
+        library.validate()?;
+        Ok(Self::with_library(settings, library))
+    }
+
     /// Detect if there is a pose in this skeleton
-    pub fn detect(&self, skeleton: &[Joint]) -> Option<Pose> {
-        let joints = joints_map(skeleton);
-        self.check_poses(joints)
+    pub fn detect<J: JointInput>(&self, skeleton: &[J]) -> Option<Pose> {
+        self.detect_explain(skeleton).pose
+    }
+
+    /// Check every pose against this skeleton and
+    /// report how each one compared
+    pub fn detect_explain<J: JointInput>(&self, skeleton: &[J]) -> Report {
+        let config = self.config();
+        let joints = joints_map(skeleton, config.settings.mirror);
+        match config.implausible(skeleton, &joints) {
+            Some(reason) => Report {
+                pose: None,
+                poses: Vec::new(),
+                rejected: Some(reason),
+            },
+            None => config.check_poses(&joints),
+        }
     }
 
-    fn detect_pose(&self, name: Pose, skeleton: &[Joint]) -> Option<f32> {
-        let mut joints = joints_map(skeleton);
-        let pose = self
+    /// Every pose checked against this skeleton, with both sides of
+    /// poses that can match either hand, before any is chosen.
+    /// Empty if the skeleton was rejected as implausible
+    pub(crate) fn check_sides<J: JointInput>(&self, skeleton: &[J]) -> Vec<PoseReport> {
+        let config = self.config();
+        let joints = joints_map(skeleton, config.settings.mirror);
+        if config.implausible(skeleton, &joints).is_some() {
+            return Vec::new();
+        }
+        config
             .poses
-            .get(&name)
-            .expect(&format!("Pose {:?} doesn't exist", name));
-        self.check_pose(pose, &mut joints)
-    }
-
-    fn check_poses(&self, mut joints: JointPos) -> Option<Pose> {
-        let mut estimation = None;
-        let mut max_closeness = std::f32::MAX;
-        for (name, pose) in &self.poses {
-            if let Some(closeness) = self.check_pose(pose, &mut joints) {
-                if closeness < max_closeness {
-                    estimation = Some(*name);
-                    max_closeness = closeness;
-                }
-            }
+            .iter()
+            .flat_map(|(&name, pose)| config.check_sides(name, pose, &joints))
+            .collect()
+    }
+
+    /// The current settings
+    pub fn settings(&self) -> Settings {
+        Settings::clone(&self.config().settings)
+    }
+
+    /// The current poses
+    pub fn poses(&self) -> Arc<PoseData> {
+        self.config().poses
+    }
+
+    /// Size of the frames the current poses were captured at
+    pub fn template_resolution(&self) -> Option<Resolution> {
+        self.config().template_resolution
+    }
+
+    /// Replace the settings.
+    /// Detections already running finish with the old settings
+    pub fn set_settings(&self, settings: Settings) {
+        self.write_config().settings = Arc::new(settings);
+    }
+
+    /// Change the current settings in place
+    pub fn update_settings<F>(&self, f: F)
+    where
+        F: FnOnce(&mut Settings),
+    {
+        let mut config = self.write_config();
+        let mut settings = Settings::clone(&config.settings);
+        f(&mut settings);
+        config.settings = Arc::new(settings);
+    }
+
+    /// Change the current settings in place if the result is valid.
+    /// Otherwise the current ones are kept
+    pub fn try_update_settings<F>(&self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut Settings),
+    {
+        let mut config = self.write_config();
+        let mut settings = Settings::clone(&config.settings);
+        f(&mut settings);
+        settings.validate()?;
+        config.settings = Arc::new(settings);
+        Ok(())
+    }
+
+    /// Replace the poses with ones captured at the same
+    /// resolution as the skeletons.
+    /// Detections already running finish with the old poses
+    pub fn set_poses(&self, poses: PoseData) {
+        self.set_library(Library::from(poses));
+    }
+
+    /// Replace the poses and the resolution they were captured at
+    pub fn set_library(&self, library: Library) {
+        self.swap(None, Some(library));
+    }
+
+    /// Replace whichever of the settings and library are given,
+    /// keeping the rest as they are at the time of the swap
+    pub(crate) fn swap(&self, settings: Option<Settings>, library: Option<Library>) {
+        let mut config = self.write_config();
+        if let Some(settings) = settings {
+            config.settings = Arc::new(settings);
+        }
+        if let Some(library) = library {
+            config.poses = Arc::new(library.poses);
+            config.template_resolution = library.resolution;
         }
-        estimation
     }
 
-    fn check_pose(&self, pose: &JointPos, joints: &JointPos) -> Option<f32> {
-        if let (Ok(mut pose_arms), Ok(mut joints_arms)) = (arms(pose), arms(joints)) {
-            let rotation = if let Some(rotation) = kabsch(&mut pose_arms, &mut joints_arms) {
-                if rotation.abs() <= self.settings.rotation_cutoff {
-                    rotation
-                } else {
-                    return None;
-                }
-            } else {
-                return None;
-            };
+    /// Replace the settings and library together
+    pub fn replace(&self, settings: Settings, library: Library) {
+        *self.write_config() = Config::new(settings, library);
+    }
 
-            if self.lasts.capture {
-                self.debug(&joints_arms, &pose_arms);
-            }
+    /// Replace the settings and library together if they're valid.
+    /// Otherwise the current ones are kept
+    pub fn try_replace(&self, settings: Settings, library: Library) -> Result<(), Error> {
+        settings.validate()?;
+        library.validate()?;
+        self.replace(settings, library);
+        Ok(())
+    }
+
+    /// Check a single pose against this skeleton and report
+    /// each joint's distance and why it failed.
+    /// Fails if the skeleton was rejected as implausible
+    pub fn diagnose<J: JointInput>(&self, name: Pose, skeleton: &[J]) -> Result<PoseReport, Error> {
+        let config = self.config();
+        let joints = joints_map(skeleton, config.settings.mirror);
+        let pose = config.poses.get(&name).ok_or(Error::UnknownPose(name))?;
+        if let Some(reason) = config.implausible(skeleton, &joints) {
+            return Err(Error::Implausible(reason));
+        }
+        Ok(config.check_either_hand(name, pose, &joints))
+    }
+
+    /// How each joint should move to make this pose,
+    /// largest move first.
+    /// None if the template couldn't be aligned
+    pub fn guidance<J: JointInput>(
+        &self,
+        name: Pose,
+        skeleton: &[J],
+    ) -> Result<Option<Vec<Correction>>, Error> {
+        self.diagnose(name, skeleton)
+            .map(|report| report.corrections())
+    }
 
-            let furthest_point = pose_arms
-                .into_iter()
-                .zip(joints_arms.into_iter())
-                .map(|(v1, v2)| glm::distance(&v1, &v2))
-                .max_by(|a, b| a.partial_cmp(b).unwrap_or(Equal));
-            if self.lasts.capture {
-                self.debug_info(rotation, &furthest_point);
+    fn config(&self) -> Config {
+        self.config
+            .read()
+            .expect("Detector config lock poisoned")
+            .clone()
+    }
+
+    fn write_config(&self) -> RwLockWriteGuard<'_, Config> {
+        self.config.write().expect("Detector config lock poisoned")
+    }
+}
+
+impl Config {
+    fn new(settings: Settings, library: Library) -> Self {
+        Config {
+            settings: Arc::new(settings),
+            poses: Arc::new(library.poses),
+            template_resolution: library.resolution,
+        }
+    }
+
+    /// Aspect ratios of the skeletons and templates.
+    /// Without a skeleton resolution neither is corrected
+    fn aspects(&self) -> (f32, f32) {
+        match self.settings.resolution {
+            Some(r) => (r.aspect(), self.template_resolution.unwrap_or(r).aspect()),
+            None => (1.0, 1.0),
+        }
+    }
+
+    /// Why the skeleton can't be a person, if plausibility is checked.
+    /// Skeletons missing arm joints are left for each pose to report
+    fn implausible<J: JointInput>(&self, skeleton: &[J], joints: &JointPos) -> Option<Implausible> {
+        let plausibility = self.settings.plausibility?;
+        let mut image = arms(joints).ok()?;
+        stretch(&mut image, self.aspects().0);
+        let real = real_arms(skeleton, self.settings.mirror);
+        plausibility.check(&image, real.as_ref().map(|r| &r[..]))
+    }
+
+    fn check_poses(&self, joints: &JointPos) -> Report {
+        let mut poses: Vec<PoseReport> = self
+            .poses
+            .iter()
+            .map(|(&name, pose)| self.check_either_hand(name, pose, joints))
+            .collect();
+        poses.sort_by(|a, b| a.rank().partial_cmp(&b.rank()).unwrap_or(Equal));
+        let pose = poses
+            .first()
+            .filter(|p| p.failure.is_none())
+            .map(|p| p.pose);
+        Report {
+            pose,
+            poses,
+            rejected: None,
+        }
+    }
+
+    /// Check a pose and, if it can match either hand,
+    /// its mirror image, keeping the closer
+    fn check_either_hand(&self, name: Pose, pose: &JointPos, joints: &JointPos) -> PoseReport {
+        self.check_sides(name, pose, joints)
+            .into_iter()
+            .min_by(|a, b| a.rank().partial_cmp(&b.rank()).unwrap_or(Equal))
+            .expect("A pose always has one side")
+    }
+
+    /// Check a pose and, if it can match either hand, its mirror image
+    fn check_sides(&self, name: Pose, pose: &JointPos, joints: &JointPos) -> Vec<PoseReport> {
+        let mut reports = vec![self.check_pose(name, pose, joints)];
+        if self.settings.either_hand.contains(&name) {
+            let mut mirrored = self.check_pose(name, &mirror_template(pose), joints);
+            mirrored.chirality = Chirality::Mirrored;
+            reports.push(mirrored);
+        }
+        reports
+    }
+
+    fn check_pose(&self, name: Pose, pose: &JointPos, joints: &JointPos) -> PoseReport {
+        let mut report = PoseReport {
+            pose: name,
+            skeleton: Vec::new(),
+            template: Vec::new(),
+            joints: Vec::new(),
+            rotation: None,
+            scale: None,
+            centre: None,
+            chirality: Chirality::Same,
+            aspect: 1.0,
+            failure: None,
+        };
+        let (mut pose_arms, mut joints_arms) = match (arms(joints), arms(pose)) {
+            (Ok(j), Ok(p)) => (p, j),
+            (_, Err(jt)) => return report.fail(Failure::IncompleteTemplate(jt)),
+            (Err(jt), _) => return report.fail(Failure::MissingJoint(jt)),
+        };
+        let (aspect, template_aspect) = self.aspects();
+        stretch(&mut joints_arms, aspect);
+        stretch(&mut pose_arms, template_aspect);
+        report.aspect = aspect;
+        let tilt = shoulders(&joints_arms).map(|s| s.angle);
+
+        let aligned = match self.settings.normalisation {
+            Normalisation::Arms => align_arms(&mut pose_arms, &mut joints_arms),
+            Normalisation::Torso => align_torso(&mut pose_arms, &mut joints_arms),
+        };
+        let Alignment {
+            rotation,
+            centre,
+            scale,
+            unit,
+        } = match aligned {
+            Some(aligned) => aligned,
+            None => return report.fail(Failure::DegenerateScale),
+        };
+        report.scale = Some(scale);
+        report.rotation = Some(rotation);
+        report.centre = Some(centre);
+        let cutoffs = self.settings.cutoffs(name);
+        let cutoff = cutoffs.joint_cutoff;
+        report.joints = ARMS
+            .iter()
+            .zip(pose_arms.iter().zip(joints_arms.iter()))
+            .map(|(&joint, (v1, v2))| JointDistance {
+                joint,
+                distance: glm::distance(v1, v2) / unit,
+                cutoff,
+            })
+            .collect();
+        report.template = pose_arms;
+        report.skeleton = joints_arms;
+
+        match (tilt, self.settings.max_tilt) {
+            (Some(tilt), Some(max)) if tilt.abs() > max => {
+                return report.fail(Failure::TorsoTilted(tilt))
             }
-            match furthest_point {
-                Some(fp) if fp < self.settings.joint_cutoff => Some(fp),
-                _ => None,
+            _ => (),
+        }
+        if !self
+            .settings
+            .rotation(name)
+            .allows(rotation, cutoffs.rotation_cutoff)
+        {
+            return report.fail(Failure::RotationExceeded(rotation));
+        }
+        match report.furthest_joint().cloned() {
+            Some(ref jd) if jd.within_cutoff() => report,
+            Some(jd) => report.fail(Failure::JointTooFar(jd.joint, jd.distance)),
+            None => report.fail(Failure::DegenerateScale),
+        }
+    }
+}
+
+impl Report {
+    /// Which way round the detected pose matched
+    pub fn chirality(&self) -> Option<Chirality> {
+        self.pose.and(self.poses.first()).map(|p| p.chirality)
+    }
+
+    /// False if no template could be compared to the skeleton,
+    /// so no pose matching doesn't mean the person isn't posing
+    pub fn evaluated(&self) -> bool {
+        self.poses.iter().any(|p| p.evaluated())
+    }
+}
+
+impl Failure {
+    /// True if the skeleton was compared and didn't match,
+    /// false if it couldn't be compared at all
+    pub fn is_mismatch(&self) -> bool {
+        match self {
+            Failure::RotationExceeded(_) | Failure::JointTooFar(..) | Failure::TorsoTilted(_) => {
+                true
             }
-        } else {
-            None
+            Failure::MissingJoint(_)
+            | Failure::IncompleteTemplate(_)
+            | Failure::DegenerateScale => false,
         }
     }
 
-    fn debug(&self, joints_arms: &Vec<Vec2>, pose_arms: &Vec<Vec2>) {
-        self.lasts.skeleton.replace(Some(joints_arms.clone()));
-        self.lasts.pose.replace(Some(pose_arms.clone()));
+    /// True if other cutoffs could have matched
+    pub(crate) fn is_cutoff(&self) -> bool {
+        matches!(
+            self,
+            Failure::RotationExceeded(_) | Failure::JointTooFar(..)
+        )
+    }
+}
+
+impl PoseReport {
+    /// Map a point from `skeleton` or `template` back into image coordinates.
+    /// None if the template couldn't be aligned
+    pub fn to_image(&self, v: &Vec2) -> Option<Vec2> {
+        let v = v + self.centre?;
+        Some(glm::vec2(v.x / self.aspect, v.y))
+    }
+
+    /// Matches first, then closest first
+    fn rank(&self) -> (bool, f32) {
+        (self.failure.is_some(), self.furthest().unwrap_or(f32::MAX))
+    }
+
+    /// Was the skeleton compared to the template,
+    /// whether it matched or not
+    pub fn evaluated(&self) -> bool {
+        self.failure.map(|f| f.is_mismatch()).unwrap_or(true)
+    }
+
+    /// Distance of the joint furthest from the template
+    pub fn furthest(&self) -> Option<f32> {
+        self.furthest_joint().map(|jd| jd.distance)
     }
 
-    fn debug_info(&self, rotation: f32, furthest_point: &Option<f32>) {
-        self.lasts.max_dist.replace(furthest_point.clone());
-        self.lasts.rotations.replace(Some(rotation));
+    /// The joint furthest from the template
+    pub fn furthest_joint(&self) -> Option<&JointDistance> {
+        self.joints
+            .iter()
+            .max_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap_or(Equal))
+    }
+
+    /// How close the furthest joint is to the template,
+    /// 1 when exact and 0 at the cutoff or beyond.
+    /// 0 if the template couldn't be aligned
+    pub fn score(&self) -> f32 {
+        match self.furthest_joint() {
+            Some(jd) if jd.cutoff > 0.0 => (1.0 - jd.distance / jd.cutoff).max(0.0),
+            _ => 0.0,
+        }
+    }
+
+    /// Joints that were over the cutoff, furthest first
+    pub fn failed_joints(&self) -> Vec<JointDistance> {
+        let mut failed: Vec<JointDistance> = self
+            .joints
+            .iter()
+            .filter(|jd| !jd.within_cutoff())
+            .cloned()
+            .collect();
+        failed.sort_by(|a, b| b.distance.partial_cmp(&a.distance).unwrap_or(Equal));
+        failed
+    }
+
+    fn fail(mut self, failure: Failure) -> Self {
+        self.failure = Some(failure);
+        self
+    }
+}
+
+impl JointDistance {
+    pub fn within_cutoff(&self) -> bool {
+        self.distance < self.cutoff
     }
 }
 
 impl Tester {
-    pub fn test_pose(settings: Settings, name: Pose, pose: JointPos) -> Self {
+    pub fn test_pose(settings: Settings, name: Pose, pose: JointPos) -> Result<Self, Error> {
         let mut poses = HashMap::new();
         poses.insert(name, pose);
-        let mut detector = Detector::with_poses(settings, poses);
-        detector.lasts.capture = true;
-        Tester { name, detector }
+        let detector = Detector::try_with_poses(settings, poses)?;
+        Ok(Tester { name, detector })
     }
 
-    pub fn test(&self, skeleton: &[Joint]) -> Option<Pose> {
-        let found = self.detector.detect_pose(self.name, skeleton);
-        found.map(|_| self.name)
+    /// The pose if it matched or None if it didn't.
+    /// Fails if the skeleton couldn't be compared to the pose
+    pub fn test<J: JointInput>(&self, skeleton: &[J]) -> Result<Option<Pose>, Error> {
+        let report = self.detector.diagnose(self.name, skeleton)?;
+        match report.failure {
+            None => Ok(Some(self.name)),
+            Some(f) if f.is_mismatch() => Ok(None),
+            Some(f) => Err(Error::NotEvaluated(f)),
+        }
     }
 }
 
-fn joints_map(skeleton: &[Joint]) -> JointPos {
+/// Check there are poses and every template has all the `ARMS`
+pub fn validate_poses(poses: &PoseData) -> Result<(), Error> {
+    if poses.is_empty() {
+        return Err(Error::EmptyLibrary);
+    }
+    for (&pose, joints) in poses {
+        arms(joints).map_err(|jt| Error::IncompleteTemplate(pose, jt))?;
+    }
+    Ok(())
+}
+
+/// A template flipped left to right
+fn mirror_template(pose: &JointPos) -> JointPos {
+    pose.iter().map(|(&t, &v)| mirror_joint(t, v)).collect()
+}
+
+fn mirror_joint(joint_type: JointType, v: Vec2) -> (JointType, Vec2) {
+    (joint_type.mirrored(), glm::vec2(1.0 - v.x, v.y))
+}
+
+/// Joints that can be compared, flipped left to right if `mirror`
+fn joints_map<J: JointInput>(skeleton: &[J], mirror: bool) -> JointPos {
     let joints = skeleton
         .iter()
-        .map(|j| (JointType::from_u32(j.type_), j))
-        .filter(|(t, _)| t.is_some())
-        .filter(|(_, j)| j.proj.x >= 0.0 && j.proj.x <= 1.0 && j.proj.y >= 0.0 && j.proj.y <= 1.0)
-        .map(|(t, j)| (t.unwrap(), glm::vec2(j.proj.x, j.proj.y)))
-        .filter(|(t, _)| available_joints(t));
+        .filter_map(|j| j.joint_type().map(|t| (t, j.position())))
+        .filter(|(_, v)| v.x >= 0.0 && v.x <= 1.0 && v.y >= 0.0 && v.y <= 1.0)
+        .filter(|(t, _)| available_joints(t))
+        .map(|(t, v)| if mirror { mirror_joint(t, v) } else { (t, v) });
     HashMap::from_iter(joints)
 }
 
+/// 3D positions of the arms in `ARMS` order,
+/// if the tracker gave them for every arm joint
+fn real_arms<J: JointInput>(skeleton: &[J], mirror: bool) -> Option<Vec<Vec3>> {
+    let real: HashMap<JointType, Vec3> = skeleton
+        .iter()
+        .filter_map(|j| {
+            let t = j.joint_type()?;
+            let t = if mirror { t.mirrored() } else { t };
+            Some((t, j.real()?))
+        })
+        .collect();
+    ARMS.iter().map(|t| real.get(t).cloned()).collect()
+}
+
 fn available_joints(joint_type: &JointType) -> bool {
     match joint_type {
         JointType::LeftShoulder
@@ -213,69 +1134,120 @@ fn available_joints(joint_type: &JointType) -> bool {
     }
 }
 
-struct JointMissing;
-
-/// Arms in order
-fn arms(joints: &JointPos) -> Result<Vec<Vec2>, JointMissing> {
-    let arms = vec![
-        joints
-            .get(&JointType::RightShoulder)
-            .map(|&j| j)
-            .ok_or(JointMissing)?,
-        joints
-            .get(&JointType::RightElbow)
-            .map(|&j| j)
-            .ok_or(JointMissing)?,
-        joints
-            .get(&JointType::RightWrist)
-            .map(|&j| j)
-            .ok_or(JointMissing)?,
-        joints
-            .get(&JointType::RightHand)
-            .map(|&j| j)
-            .ok_or(JointMissing)?,
-        joints
-            .get(&JointType::LeftShoulder)
-            .map(|&j| j)
-            .ok_or(JointMissing)?,
-        joints
-            .get(&JointType::LeftElbow)
-            .map(|&j| j)
-            .ok_or(JointMissing)?,
-        joints
-            .get(&JointType::LeftWrist)
-            .map(|&j| j)
-            .ok_or(JointMissing)?,
-        joints
-            .get(&JointType::LeftHand)
-            .map(|&j| j)
-            .ok_or(JointMissing)?,
-    ];
+pub(crate) fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
 
-    Ok(arms)
+/// The joints compared against each template, in order
+pub const ARMS: [JointType; 8] = [
+    JointType::RightShoulder,
+    JointType::RightElbow,
+    JointType::RightWrist,
+    JointType::RightHand,
+    JointType::LeftShoulder,
+    JointType::LeftElbow,
+    JointType::LeftWrist,
+    JointType::LeftHand,
+];
+
+/// How much shorter, in shoulder widths, the hands and wrists
+/// have to be when swapped before the hands count as swapped
+const HANDS_SWAPPED_MARGIN: f32 = 0.5;
+
+/// How a template was lined up with a skeleton
+struct Alignment {
+    /// Radians the template was rotated by
+    rotation: f32,
+    /// Where the skeleton was centred from
+    centre: Vec2,
+    /// Template size over skeleton size
+    scale: f32,
+    /// Length that distances are measured in
+    unit: f32,
 }
 
-fn kabsch(a: &mut [Vec2], b: &mut [Vec2]) -> Option<f32> {
-    let scale = get_scale(a, b);
-    let scale = if scale.is_none() {
-        return scale;
-    } else {
-        scale.unwrap()
-    };
+/// The line from the right shoulder to the left
+struct Shoulders {
+    middle: Vec2,
+    width: f32,
+    /// Radians from level
+    angle: f32,
+}
+
+/// Arms in order, or the first missing joint
+fn arms(joints: &JointPos) -> Result<Vec<Vec2>, JointType> {
+    ARMS.iter()
+        .map(|jt| joints.get(jt).cloned().ok_or(*jt))
+        .collect()
+}
 
+/// Fit the template arms `a` to the skeleton arms `b`
+fn align_arms(a: &mut [Vec2], b: &mut [Vec2]) -> Option<Alignment> {
+    let scale = get_scale(a, b)?;
+    let (rotation, centre) = kabsch(a, b, scale)?;
+    Some(Alignment {
+        rotation,
+        centre,
+        scale,
+        unit: 1.0,
+    })
+}
+
+/// Line up the template arms `a` with the skeleton arms `b` by their
+/// shoulders, centring `b` between its shoulders.
+/// Distances are measured in `b`'s shoulder widths
+fn align_torso(a: &mut [Vec2], b: &mut [Vec2]) -> Option<Alignment> {
+    let (sa, sb) = (shoulders(a)?, shoulders(b)?);
+    let rotation = na::Rotation2::new(sb.angle - sa.angle);
+    let scale = sb.width / sa.width;
+    for v in a.iter_mut() {
+        *v = rotation * (*v - sa.middle) * scale;
+    }
+    for v in b.iter_mut() {
+        *v -= sb.middle;
+    }
+    Some(Alignment {
+        rotation: rotation.angle(),
+        centre: sb.middle,
+        scale: sa.width / sb.width,
+        unit: sb.width,
+    })
+}
+
+/// None if the shoulders are in the same place
+fn shoulders(arms: &[Vec2]) -> Option<Shoulders> {
+    let (right, left) = (arms[0], arms[4]);
+    let line = left - right;
+    let width = glm::length(&line);
+    if width <= 0.0 {
+        return None;
+    }
+    Some(Shoulders {
+        middle: (left + right) / 2.0,
+        width,
+        angle: line.y.atan2(line.x),
+    })
+}
+
+/// Scales, centers and rotates `a` onto `b`, centering `b`.
+/// Returns the angle of rotation and the original center of `b`
+fn kabsch(a: &mut [Vec2], b: &mut [Vec2], scale: f32) -> Option<(f32, Vec2)> {
     let mut ma = make_mat(a);
     ma /= scale;
 
     let mut mb = make_mat(b);
 
-    center(&mut ma, &mut mb);
+    let centre = center(&mut ma, &mut mb);
 
     write_joints(b, &mb);
 
     rotation_matrix(&ma, &mb).map(|(r, angle)| {
         let mad = r * ma;
         write_joints(a, &mad);
-        angle
+        (angle, centre)
     })
 }
 
@@ -292,11 +1264,19 @@ fn get_scale(a: &[Vec2], b: &[Vec2]) -> Option<f32> {
     }
 }
 
+/// Multiply x by the aspect ratio
+fn stretch(a: &mut [Vec2], aspect: f32) {
+    for v in a.iter_mut() {
+        v.x *= aspect;
+    }
+}
+
 fn make_mat(a: &[Vec2]) -> Mat2x8 {
     Mat2x8::from_columns(&a[..])
 }
 
-fn center(ma: &mut Mat2x8, mb: &mut Mat2x8) {
+/// Centers both matrices, returning the original center of `mb`
+fn center(ma: &mut Mat2x8, mb: &mut Mat2x8) -> Vec2 {
     let mut a_ctr = glm::vec2(0.0, 0.0);
     let mut b_ctr = glm::vec2(0.0, 0.0);
 
@@ -314,6 +1294,7 @@ fn center(ma: &mut Mat2x8, mb: &mut Mat2x8) {
         col_b -= b_ctr;
         *mb = glm::set_column(&mb, i, &col_b);
     }
+    b_ctr
 }
 
 fn write_joints(a: &mut [Vec2], m: &Mat2x8) {
@@ -326,15 +1307,15 @@ fn rotation_matrix(ma: &Mat2x8, mb: &Mat2x8) -> Option<(Mat2x2, f32)> {
     let cov = mb * ma.transpose();
     let svd = cov.svd(true, true);
 
-    if let (Some(ref v), Some(ref u)) = (svd.v_t, svd.u) {
-        let d = (v * u.transpose()).determinant();
+    if let (Some(ref v_t), Some(ref u)) = (svd.v_t, svd.u) {
+        let d = (u * v_t).determinant();
         let d = if d > 0.0 { 1.0 } else { -1.0 };
         let i = glm::identity::<f32, glm::U2>();
         let mut col_i = glm::column(&i, 1);
         col_i[1] = d;
         let i = glm::set_column(&i, 1, &col_i);
 
-        let r = v * i * u.transpose();
+        let r = u * i * v_t;
         let rot = na::Rotation2::from_matrix_unchecked(r);
         Some((r, rot.angle()))
     } else {
@@ -0,0 +1,111 @@
+//! Save and load pose libraries as JSON.
+//!
+//! A library maps pose names to joint positions:
+//!
+//! ```json
+//! {"DabR": {"LeftShoulder": [0.68, 0.49], "LeftElbow": [0.58, 0.53], ...}, ...}
+//! ```
+//!
+//! Templates captured on a non-square frame can say at what size,
+//! otherwise they're taken to match the skeletons:
+//!
+//! ```json
+//! {"resolution": {"width": 640, "height": 480}, "poses": {"DabR": {...}, ...}}
+//! ```
+
+use crate::{glm, invalid_data, JointPos, JointType, Library, Pose, PoseData, Resolution};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read};
+use std::path::Path;
+
+type LibraryRecord = BTreeMap<String, BTreeMap<String, [f32; 2]>>;
+
+/// A library is either just the poses, or the poses
+/// and the resolution they were captured at
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum LibraryFile {
+    Captured {
+        resolution: Resolution,
+        poses: LibraryRecord,
+    },
+    Poses(LibraryRecord),
+}
+
+/// Load a pose library
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Library> {
+    read(BufReader::new(File::open(path)?))
+}
+
+pub fn read<R: Read>(reader: R) -> io::Result<Library> {
+    from_file(serde_json::from_reader(reader).map_err(invalid_data)?)
+}
+
+pub fn from_str(s: &str) -> io::Result<Library> {
+    from_file(serde_json::from_str(s).map_err(invalid_data)?)
+}
+
+/// Save a pose library
+pub fn save<P: AsRef<Path>>(library: &Library, path: P) -> io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(file, &to_file(library)).map_err(io::Error::from)
+}
+
+pub fn to_string(library: &Library) -> String {
+    serde_json::to_string_pretty(&to_file(library)).expect("Pose libraries always serialize")
+}
+
+fn from_file(file: LibraryFile) -> io::Result<Library> {
+    let (resolution, record) = match file {
+        LibraryFile::Captured { resolution, poses } => (Some(resolution), poses),
+        LibraryFile::Poses(poses) => (None, poses),
+    };
+    Ok(Library {
+        poses: from_record(record)?,
+        resolution,
+    })
+}
+
+fn to_file(library: &Library) -> LibraryFile {
+    let poses = to_record(&library.poses);
+    match library.resolution {
+        Some(resolution) => LibraryFile::Captured { resolution, poses },
+        None => LibraryFile::Poses(poses),
+    }
+}
+
+fn from_record(record: LibraryRecord) -> io::Result<PoseData> {
+    record
+        .into_iter()
+        .map(|(name, joints)| {
+            let pose = Pose::from_name(&name)
+                .ok_or_else(|| invalid_data(format!("Unknown pose {}", name)))?;
+            let joints = joints
+                .into_iter()
+                .map(|(joint, v)| {
+                    JointType::from_name(&joint)
+                        .map(|t| (t, glm::vec2(v[0], v[1])))
+                        .ok_or_else(|| {
+                            invalid_data(format!("Unknown joint type {} in {}", joint, name))
+                        })
+                })
+                .collect::<io::Result<JointPos>>()?;
+            Ok((pose, joints))
+        })
+        .collect()
+}
+
+fn to_record(poses: &PoseData) -> LibraryRecord {
+    poses
+        .iter()
+        .map(|(pose, joints)| {
+            let joints = joints
+                .iter()
+                .map(|(joint, v)| (joint.name().to_string(), [v.x, v.y]))
+                .collect();
+            (pose.name().to_string(), joints)
+        })
+        .collect()
+}
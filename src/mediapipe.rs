@@ -0,0 +1,142 @@
+//! Convert MediaPipe Pose landmarks into joints.
+//!
+//! MediaPipe's 33 landmarks are already normalised to the image
+//! like Nuitrack's `proj`. Visibility becomes the joint confidence
+//! and hands are placed in the middle of the wrist, index and pinky.
+//!
+//! With the `json` feature landmark dumps can be read from disk.
+//! A dump is a frame or a list of frames, where a frame is a list of
+//! 33 landmarks or an object with them under `landmarks`:
+//!
+//! ```json
+//! [{"landmarks": [{"x": 0.51, "y": 0.23, "z": -0.4, "visibility": 0.99}, ...]}, ...]
+//! ```
+
+use crate::{glm, Joint, JointType};
+
+/// A single MediaPipe Pose landmark
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Landmark {
+    pub x: f32,
+    pub y: f32,
+    /// Depth with the hips at zero
+    pub z: f32,
+    pub visibility: f32,
+}
+
+/// Joints found directly at a landmark
+const LANDMARKS: [(usize, JointType); 13] = [
+    (0, JointType::Head),
+    (11, JointType::LeftShoulder),
+    (12, JointType::RightShoulder),
+    (13, JointType::LeftElbow),
+    (14, JointType::RightElbow),
+    (15, JointType::LeftWrist),
+    (16, JointType::RightWrist),
+    (23, JointType::LeftHip),
+    (24, JointType::RightHip),
+    (25, JointType::LeftKnee),
+    (26, JointType::RightKnee),
+    (27, JointType::LeftAnkle),
+    (28, JointType::RightAnkle),
+];
+
+/// Wrist, pinky and index landmarks of each hand
+const HANDS: [(JointType, [usize; 3]); 2] = [
+    (JointType::LeftHand, [15, 17, 19]),
+    (JointType::RightHand, [16, 18, 20]),
+];
+
+/// Number of landmarks MediaPipe Pose gives
+pub const LANDMARK_COUNT: usize = 33;
+
+/// Joints from a frame of landmarks.
+/// Returns None if there aren't 33 landmarks
+pub fn joints(landmarks: &[Landmark]) -> Option<Vec<Joint>> {
+    if landmarks.len() != LANDMARK_COUNT {
+        return None;
+    }
+    let joint = |joint_type, l: &Landmark| Joint {
+        joint_type,
+        proj: glm::vec3(l.x, l.y, l.z),
+        real: glm::vec3(0.0, 0.0, 0.0),
+        confidence: l.visibility,
+    };
+    let mut joints: Vec<Joint> = LANDMARKS
+        .iter()
+        .map(|&(i, joint_type)| joint(joint_type, &landmarks[i]))
+        .collect();
+    for &(hand, ref points) in HANDS.iter() {
+        let points: Vec<&Landmark> = points.iter().map(|&i| &landmarks[i]).collect();
+        let count = points.len() as f32;
+        let mean = Landmark {
+            x: points.iter().map(|l| l.x).sum::<f32>() / count,
+            y: points.iter().map(|l| l.y).sum::<f32>() / count,
+            z: points.iter().map(|l| l.z).sum::<f32>() / count,
+            visibility: points.iter().map(|l| l.visibility).fold(1.0, f32::min),
+        };
+        joints.push(joint(hand, &mean));
+    }
+    Some(joints)
+}
+
+#[cfg(feature = "json")]
+pub use self::json::{load, read};
+
+#[cfg(feature = "json")]
+mod json {
+    use super::Landmark;
+    use crate::{invalid_data, Joint};
+    use serde::Deserialize;
+    use std::fs::File;
+    use std::io::{self, BufReader, Read};
+    use std::path::Path;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Dump {
+        Frames(Vec<FrameRecord>),
+        Frame(FrameRecord),
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum FrameRecord {
+        Landmarks(Vec<Landmark>),
+        Object {
+            #[serde(alias = "pose_landmarks")]
+            landmarks: Vec<Landmark>,
+        },
+    }
+
+    /// Load a landmark dump, one list of joints per frame
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Vec<Vec<Joint>>> {
+        read(BufReader::new(File::open(path)?))
+    }
+
+    /// Read a landmark dump, one list of joints per frame
+    pub fn read<R: Read>(reader: R) -> io::Result<Vec<Vec<Joint>>> {
+        let frames = match serde_json::from_reader(reader).map_err(invalid_data)? {
+            Dump::Frames(frames) => frames,
+            Dump::Frame(frame) => vec![frame],
+        };
+        frames
+            .iter()
+            .enumerate()
+            .map(|(n, frame)| {
+                let landmarks = match frame {
+                    FrameRecord::Landmarks(l) | FrameRecord::Object { landmarks: l } => l,
+                };
+                super::joints(landmarks).ok_or_else(|| {
+                    invalid_data(format!(
+                        "frame {} has {} landmarks, MediaPipe Pose has {}",
+                        n,
+                        landmarks.len(),
+                        super::LANDMARK_COUNT
+                    ))
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,50 @@
+//! Use Nuitrack skeletons directly or convert them to this crate's.
+
+use crate::nui;
+use crate::{glm, Joint, JointInput, JointType, Skeleton, Vec2};
+
+impl JointInput for nui::Joint {
+    fn joint_type(&self) -> Option<JointType> {
+        JointType::from_u32(self.type_)
+    }
+
+    fn position(&self) -> Vec2 {
+        glm::vec2(self.proj.x, self.proj.y)
+    }
+
+    fn real(&self) -> Option<glm::Vec3> {
+        Some(glm::vec3(self.real.x, self.real.y, self.real.z))
+    }
+}
+
+impl Joint {
+    /// None if Nuitrack gave a joint type this crate doesn't know
+    pub fn from_nuitrack(joint: &nui::Joint) -> Option<Self> {
+        Some(Joint {
+            joint_type: JointType::from_u32(joint.type_)?,
+            proj: glm::vec3(joint.proj.x, joint.proj.y, joint.proj.z),
+            real: glm::vec3(joint.real.x, joint.real.y, joint.real.z),
+            confidence: joint.confidence,
+        })
+    }
+}
+
+impl<'a> From<&'a nui::SkeletonFeed> for Skeleton {
+    /// Joints this crate doesn't know are left out
+    fn from(skeleton: &'a nui::SkeletonFeed) -> Self {
+        Skeleton {
+            id: skeleton.id,
+            joints: skeleton
+                .joints
+                .iter()
+                .filter_map(Joint::from_nuitrack)
+                .collect(),
+        }
+    }
+}
+
+impl From<nui::SkeletonFeed> for Skeleton {
+    fn from(skeleton: nui::SkeletonFeed) -> Self {
+        Skeleton::from(&skeleton)
+    }
+}
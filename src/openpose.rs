@@ -0,0 +1,225 @@
+//! Convert OpenPose BODY_25 and COCO keypoints into joints.
+//!
+//! Keypoints are flat `[x, y, confidence, ...]` lists in pixels.
+//! They're divided by the image size to match Nuitrack's `proj`.
+//! Neither format has hands so they're carried on from the wrists.
+//!
+//! With the `json` feature OpenPose's `--write_json` output and
+//! COCO keypoint annotations can be read from disk.
+
+use crate::skeleton::add_hands;
+use crate::{glm, Joint, JointType};
+
+/// Joint for each BODY_25 keypoint
+const BODY_25: [Option<JointType>; 25] = [
+    Some(JointType::Head), // Nose
+    Some(JointType::Neck),
+    Some(JointType::RightShoulder),
+    Some(JointType::RightElbow),
+    Some(JointType::RightWrist),
+    Some(JointType::LeftShoulder),
+    Some(JointType::LeftElbow),
+    Some(JointType::LeftWrist),
+    Some(JointType::Waist), // MidHip
+    Some(JointType::RightHip),
+    Some(JointType::RightKnee),
+    Some(JointType::RightAnkle),
+    Some(JointType::LeftHip),
+    Some(JointType::LeftKnee),
+    Some(JointType::LeftAnkle),
+    None,                       // REye
+    None,                       // LEye
+    None,                       // REar
+    None,                       // LEar
+    Some(JointType::LeftFoot),  // LBigToe
+    None,                       // LSmallToe
+    None,                       // LHeel
+    Some(JointType::RightFoot), // RBigToe
+    None,                       // RSmallToe
+    None,                       // RHeel
+];
+
+/// Joint for each COCO keypoint
+const COCO: [Option<JointType>; 17] = [
+    Some(JointType::Head), // nose
+    None,                  // left_eye
+    None,                  // right_eye
+    None,                  // left_ear
+    None,                  // right_ear
+    Some(JointType::LeftShoulder),
+    Some(JointType::RightShoulder),
+    Some(JointType::LeftElbow),
+    Some(JointType::RightElbow),
+    Some(JointType::LeftWrist),
+    Some(JointType::RightWrist),
+    Some(JointType::LeftHip),
+    Some(JointType::RightHip),
+    Some(JointType::LeftKnee),
+    Some(JointType::RightKnee),
+    Some(JointType::LeftAnkle),
+    Some(JointType::RightAnkle),
+];
+
+/// Joints from OpenPose BODY_25 keypoints.
+/// Keypoints with zero confidence weren't found and are left out
+pub fn body_25(keypoints: &[f32], width: f32, height: f32) -> Vec<Joint> {
+    convert(&BODY_25, keypoints, width, height, |c| c)
+}
+
+/// Joints from COCO keypoints.
+/// Unlabelled keypoints are left out and occluded
+/// ones are given half confidence
+pub fn coco(keypoints: &[f32], width: f32, height: f32) -> Vec<Joint> {
+    convert(&COCO, keypoints, width, height, |v| v / 2.0)
+}
+
+fn convert<F>(
+    layout: &[Option<JointType>],
+    keypoints: &[f32],
+    width: f32,
+    height: f32,
+    confidence: F,
+) -> Vec<Joint>
+where
+    F: Fn(f32) -> f32,
+{
+    let mut joints: Vec<Joint> = keypoints
+        .chunks(3)
+        .zip(layout.iter())
+        .filter(|(k, _)| k.len() == 3)
+        .filter_map(|(k, &joint_type)| {
+            let confidence = confidence(k[2]);
+            if confidence <= 0.0 {
+                return None;
+            }
+            Some(Joint {
+                joint_type: joint_type?,
+                proj: glm::vec3(k[0] / width, k[1] / height, 0.0),
+                real: glm::vec3(0.0, 0.0, 0.0),
+                confidence,
+            })
+        })
+        .collect();
+    add_hands(&mut joints);
+    joints
+}
+
+#[cfg(feature = "json")]
+pub use self::json::{load, load_coco, read, read_coco, Annotation};
+
+#[cfg(feature = "json")]
+mod json {
+    use crate::{invalid_data, Skeleton};
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+    use std::fs::File;
+    use std::io::{self, BufReader, Read};
+    use std::path::Path;
+
+    /// A person from a COCO keypoint annotation
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct Annotation {
+        pub image_id: u64,
+        /// Has the annotation's id
+        pub skeleton: Skeleton,
+    }
+
+    #[derive(Deserialize)]
+    struct OpenPoseFrame {
+        people: Vec<OpenPosePerson>,
+    }
+
+    #[derive(Deserialize)]
+    struct OpenPosePerson {
+        pose_keypoints_2d: Vec<f32>,
+    }
+
+    #[derive(Deserialize)]
+    struct CocoDataset {
+        images: Vec<CocoImage>,
+        annotations: Vec<CocoAnnotation>,
+    }
+
+    #[derive(Deserialize)]
+    struct CocoImage {
+        id: u64,
+        width: f32,
+        height: f32,
+    }
+
+    #[derive(Deserialize)]
+    struct CocoAnnotation {
+        id: u64,
+        image_id: u64,
+        keypoints: Vec<f32>,
+    }
+
+    /// Load one frame of OpenPose `--write_json` output.
+    /// OpenPose doesn't save the image size so it has to be given
+    pub fn load<P: AsRef<Path>>(path: P, width: f32, height: f32) -> io::Result<Vec<Skeleton>> {
+        read(BufReader::new(File::open(path)?), width, height)
+    }
+
+    /// Read one frame of OpenPose BODY_25 output.
+    /// Skeleton ids are the order people appear in
+    pub fn read<R: Read>(reader: R, width: f32, height: f32) -> io::Result<Vec<Skeleton>> {
+        let frame: OpenPoseFrame = serde_json::from_reader(reader).map_err(invalid_data)?;
+        frame
+            .people
+            .iter()
+            .enumerate()
+            .map(|(id, person)| {
+                let keypoints = &person.pose_keypoints_2d;
+                if keypoints.len() != 75 {
+                    return Err(invalid_data(format!(
+                        "person {} has {} keypoint values, BODY_25 has 75",
+                        id,
+                        keypoints.len()
+                    )));
+                }
+                Ok(Skeleton {
+                    id: id as i32,
+                    joints: super::body_25(keypoints, width, height),
+                })
+            })
+            .collect()
+    }
+
+    /// Load COCO keypoint annotations
+    pub fn load_coco<P: AsRef<Path>>(path: P) -> io::Result<Vec<Annotation>> {
+        read_coco(BufReader::new(File::open(path)?))
+    }
+
+    /// Read COCO keypoint annotations,
+    /// normalised by the size of their images
+    pub fn read_coco<R: Read>(reader: R) -> io::Result<Vec<Annotation>> {
+        let dataset: CocoDataset = serde_json::from_reader(reader).map_err(invalid_data)?;
+        let images: HashMap<u64, &CocoImage> = dataset.images.iter().map(|i| (i.id, i)).collect();
+        dataset
+            .annotations
+            .iter()
+            .map(|a| {
+                let image = images.get(&a.image_id).ok_or_else(|| {
+                    invalid_data(format!(
+                        "annotation {} has unknown image {}",
+                        a.id, a.image_id
+                    ))
+                })?;
+                if a.keypoints.len() != 51 {
+                    return Err(invalid_data(format!(
+                        "annotation {} has {} keypoint values, COCO has 51",
+                        a.id,
+                        a.keypoints.len()
+                    )));
+                }
+                Ok(Annotation {
+                    image_id: a.image_id,
+                    skeleton: Skeleton {
+                        id: a.id as i32,
+                        joints: super::coco(&a.keypoints, image.width, image.height),
+                    },
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,224 @@
+//! Send detections, scores and events over OSC.
+//!
+//! Each frame sends, for every user:
+//! - the detected pose name, or `none`, to `detection`
+//! - every pose's score (see `PoseReport::score`) to `score`
+//! - an event name and pose, if any, to `event` when something changes
+//!
+//! Addresses can contain `{user}`, `{pose}` and `{event}`
+//! which are filled in before sending.
+
+use crate::tracker::{Event, Tracker};
+use crate::{invalid_data, Detector, Report, Skeleton};
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+/// An OSC message
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Message {
+    pub address: String,
+    pub args: Vec<Arg>,
+}
+
+/// An OSC argument
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum Arg {
+    Int(i32),
+    Float(f32),
+    Str(String),
+}
+
+/// Where each kind of message is sent
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Addresses {
+    pub detection: String,
+    pub score: String,
+    pub event: String,
+}
+
+/// Sends OSC messages over UDP
+pub struct Sender {
+    socket: UdpSocket,
+    target: SocketAddr,
+    pub addresses: Addresses,
+    tracker: Tracker,
+}
+
+impl Default for Addresses {
+    fn default() -> Self {
+        Addresses {
+            detection: "/pose/{user}".to_string(),
+            score: "/pose/{user}/{pose}".to_string(),
+            event: "/event/{user}/{event}".to_string(),
+        }
+    }
+}
+
+impl Sender {
+    /// Send to this address from any local port
+    pub fn new<A: ToSocketAddrs>(target: A) -> io::Result<Self> {
+        let target = target
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No address to send to"))?;
+        let local = if target.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        Ok(Sender {
+            socket: UdpSocket::bind(local)?,
+            target,
+            addresses: Addresses::default(),
+            tracker: Tracker::new(),
+        })
+    }
+
+    /// Detect poses in a frame of skeletons and send the
+    /// detections, scores and any events. Returns the events
+    pub fn send_frame(
+        &mut self,
+        detector: &Detector,
+        skeletons: &[Skeleton],
+    ) -> io::Result<Vec<Event>> {
+        let mut detections = Vec::with_capacity(skeletons.len());
+        for skeleton in skeletons {
+            let report = detector.detect_explain(&skeleton.joints);
+            self.send_report(skeleton.id, &report)?;
+            detections.push((skeleton.id, report.pose));
+        }
+        let events = self.tracker.update(detections);
+        for event in &events {
+            self.send_event(event)?;
+        }
+        Ok(events)
+    }
+
+    /// Send a user's detection and the score of every pose
+    pub fn send_report(&self, user: i32, report: &Report) -> io::Result<()> {
+        let detected = report.pose.map(|p| p.name()).unwrap_or("none");
+        self.send(&Message {
+            address: fill(&self.addresses.detection, user, detected, ""),
+            args: vec![Arg::Str(detected.to_string())],
+        })?;
+        for pose in &report.poses {
+            self.send(&Message {
+                address: fill(&self.addresses.score, user, pose.pose.name(), ""),
+                args: vec![Arg::Float(pose.score())],
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn send_event(&self, event: &Event) -> io::Result<()> {
+        let pose = event.pose().map(|p| p.name()).unwrap_or("");
+        let mut args = vec![Arg::Str(event.name().to_string())];
+        if !pose.is_empty() {
+            args.push(Arg::Str(pose.to_string()));
+        }
+        self.send(&Message {
+            address: fill(&self.addresses.event, event.user(), pose, event.name()),
+            args,
+        })
+    }
+
+    pub fn send(&self, message: &Message) -> io::Result<()> {
+        self.socket
+            .send_to(&message.encode(), self.target)
+            .map(|_| ())
+    }
+}
+
+fn fill(address: &str, user: i32, pose: &str, event: &str) -> String {
+    address
+        .replace("{user}", &user.to_string())
+        .replace("{pose}", pose)
+        .replace("{event}", event)
+}
+
+impl Message {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_string(&mut bytes, &self.address);
+        let tags: String = Some(',')
+            .into_iter()
+            .chain(self.args.iter().map(|a| match a {
+                Arg::Int(_) => 'i',
+                Arg::Float(_) => 'f',
+                Arg::Str(_) => 's',
+            }))
+            .collect();
+        write_string(&mut bytes, &tags);
+        for arg in &self.args {
+            match arg {
+                Arg::Int(i) => write_word(&mut bytes, *i as u32),
+                Arg::Float(f) => write_word(&mut bytes, f.to_bits()),
+                Arg::Str(s) => write_string(&mut bytes, s),
+            }
+        }
+        bytes
+    }
+
+    /// Decode a message with int, float and string arguments
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let mut rest = bytes;
+        let address = read_string(&mut rest)?;
+        let tags = read_string(&mut rest)?;
+        if !tags.starts_with(',') {
+            return Err(invalid_data("OSC type tags must start with ,"));
+        }
+        let args = tags[1..]
+            .chars()
+            .map(|tag| match tag {
+                'i' => read_word(&mut rest).map(|w| Arg::Int(w as i32)),
+                'f' => read_word(&mut rest).map(|w| Arg::Float(f32::from_bits(w))),
+                's' => read_string(&mut rest).map(Arg::Str),
+                t => Err(invalid_data(format!("Unsupported OSC type tag {}", t))),
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Message { address, args })
+    }
+}
+
+/// Null terminated and padded to four bytes
+fn write_string(bytes: &mut Vec<u8>, s: &str) {
+    bytes.extend_from_slice(s.as_bytes());
+    let padding = 4 - s.len() % 4;
+    bytes.extend((0..padding).map(|_| 0));
+}
+
+fn read_string(bytes: &mut &[u8]) -> io::Result<String> {
+    let end = bytes
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or_else(|| invalid_data("OSC string isn't terminated"))?;
+    let s = String::from_utf8(bytes[..end].to_vec()).map_err(invalid_data)?;
+    let padded = (end / 4 + 1) * 4;
+    *bytes = &bytes[padded.min(bytes.len())..];
+    Ok(s)
+}
+
+/// Big endian
+fn write_word(bytes: &mut Vec<u8>, word: u32) {
+    bytes.extend_from_slice(&[
+        (word >> 24) as u8,
+        (word >> 16) as u8,
+        (word >> 8) as u8,
+        word as u8,
+    ]);
+}
+
+fn read_word(bytes: &mut &[u8]) -> io::Result<u32> {
+    if bytes.len() < 4 {
+        return Err(invalid_data("OSC message is too short"));
+    }
+    let word = u32::from(bytes[0]) << 24
+        | u32::from(bytes[1]) << 16
+        | u32::from(bytes[2]) << 8
+        | u32::from(bytes[3]);
+    *bytes = &bytes[4..];
+    Ok(word)
+}
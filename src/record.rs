@@ -0,0 +1,74 @@
+use crate::{glm, invalid_data, Joint, JointType, Settings};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+/// A joint as it's stored on disk
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct JointRecord {
+    #[serde(rename = "type")]
+    pub joint: String,
+    pub proj: [f32; 3],
+    #[serde(default)]
+    pub real: [f32; 3],
+    #[serde(default = "full_confidence")]
+    pub confidence: f32,
+}
+
+fn full_confidence() -> f32 {
+    1.0
+}
+
+impl JointRecord {
+    pub fn from_joint(joint: &Joint) -> Self {
+        JointRecord {
+            joint: joint.joint_type.name().to_string(),
+            proj: [joint.proj.x, joint.proj.y, joint.proj.z],
+            real: [joint.real.x, joint.real.y, joint.real.z],
+            confidence: joint.confidence,
+        }
+    }
+
+    pub fn to_joint(&self) -> io::Result<Joint> {
+        let joint_type = JointType::from_name(&self.joint)
+            .ok_or_else(|| invalid_data(format!("Unknown joint type {}", self.joint)))?;
+        Ok(Joint {
+            joint_type,
+            proj: glm::vec3(self.proj[0], self.proj[1], self.proj[2]),
+            real: glm::vec3(self.real[0], self.real[1], self.real[2]),
+            confidence: self.confidence,
+        })
+    }
+}
+
+/// Joints are written the same way everywhere
+/// so recordings, labelled frames and messages agree
+impl Serialize for Joint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        JointRecord::from_joint(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Joint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        JointRecord::deserialize(deserializer)?
+            .to_joint()
+            .map_err(D::Error::custom)
+    }
+}
+
+impl Settings {
+    /// Load settings from a JSON file
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = BufReader::new(File::open(path)?);
+        serde_json::from_reader(file).map_err(invalid_data)
+    }
+
+    /// Save settings to a JSON file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(file, self).map_err(io::Error::from)
+    }
+}
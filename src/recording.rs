@@ -0,0 +1,152 @@
+//! Record skeletons to a simple file format and replay them
+//! without needing the Nuitrack SDK.
+//!
+//! Recordings are JSON Lines with one frame per line:
+//!
+//! ```json
+//! {"timestamp": 0.033, "skeletons": [{"id": 1, "joints": [
+//!     {"type": "LeftShoulder", "proj": [0.68, 0.49, 2.1], "real": [120.0, 30.0, 2100.0], "confidence": 0.75},
+//!     ...
+//! ]}]}
+//! ```
+//!
+//! `timestamp` is seconds since the recording started.
+
+use crate::{invalid_data, Detector, Pose, Skeleton};
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// A frame of skeletons read from a recording
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// Seconds since the recording started
+    pub timestamp: f64,
+    pub skeletons: Vec<Skeleton>,
+}
+
+/// Writes skeletons to a recording.
+/// Move it into a `skeleton_data` callback to record live.
+pub struct Recorder<W: Write> {
+    writer: W,
+    start: Instant,
+}
+
+/// Reads frames back from a recording
+pub struct Reader<R: BufRead> {
+    lines: Lines<R>,
+    line: usize,
+}
+
+/// A `RecordedFrame` that borrows its skeletons
+#[derive(Serialize)]
+struct FrameRef<'a> {
+    timestamp: f64,
+    skeletons: Vec<&'a Skeleton>,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Start a new recording at this path
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Start a new recording. Timestamps are measured from now
+    pub fn new(writer: W) -> Self {
+        Recorder {
+            writer,
+            start: Instant::now(),
+        }
+    }
+
+    /// Write a frame of skeletons timestamped with the time since the recording started
+    pub fn write<I>(&mut self, skeletons: I) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Skeleton>,
+    {
+        let elapsed = self.start.elapsed();
+        let timestamp = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_nanos()) * 1e-9;
+        self.write_at(timestamp, skeletons)
+    }
+
+    /// Write a frame of skeletons with a given timestamp in seconds
+    pub fn write_at<I>(&mut self, timestamp: f64, skeletons: I) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Skeleton>,
+    {
+        let skeletons: Vec<I::Item> = skeletons.into_iter().collect();
+        let frame = FrameRef {
+            timestamp,
+            skeletons: skeletons.iter().map(Borrow::borrow).collect(),
+        };
+        serde_json::to_writer(&mut self.writer, &frame)?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Flush buffered frames to the writer
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl Reader<BufReader<File>> {
+    /// Open a recording
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn new(reader: R) -> Self {
+        Reader {
+            lines: reader.lines(),
+            line: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = io::Result<RecordedFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.line += 1;
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e)),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(
+                parse_frame(&line).map_err(|e| invalid_data(format!("line {}: {}", self.line, e))),
+            );
+        }
+    }
+}
+
+fn parse_frame(line: &str) -> io::Result<RecordedFrame> {
+    serde_json::from_str(line).map_err(invalid_data)
+}
+
+impl RecordedFrame {
+    /// Detect poses for every skeleton in this frame,
+    /// paired with the skeleton id
+    pub fn detect(&self, detector: &Detector) -> Vec<(i32, Option<Pose>)> {
+        self.skeletons
+            .iter()
+            .map(|s| (s.id, detector.detect(&s.joints)))
+            .collect()
+    }
+}
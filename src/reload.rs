@@ -0,0 +1,164 @@
+//! Reload a settings file and pose library into a running
+//! detector when they change on disk.
+//!
+//! Files are polled, re-reading them and comparing their contents
+//! with what was last loaded. Changed files are checked before
+//! they're swapped in, and only the ones that changed are swapped.
+//! A file that fails to load or check is rejected and the detector
+//! keeps what it had.
+
+use crate::{invalid_data, library, Detector, Settings};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Watches files and reloads them into a detector
+pub struct Reloader {
+    detector: Arc<Detector>,
+    settings: Option<Watched>,
+    poses: Option<Watched>,
+}
+
+/// A reloader polling on its own thread.
+/// Stops when dropped
+pub struct Watcher {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct Watched {
+    path: PathBuf,
+    /// Contents when last loaded or rejected
+    contents: Option<Vec<u8>>,
+}
+
+impl Reloader {
+    /// Reload into this detector.
+    /// Nothing is watched until `watch_settings` or `watch_poses`
+    pub fn new(detector: Arc<Detector>) -> Self {
+        Reloader {
+            detector,
+            settings: None,
+            poses: None,
+        }
+    }
+
+    /// Watch a settings file, see `Settings::load`
+    pub fn watch_settings<P: AsRef<Path>>(&mut self, path: P) {
+        self.settings = Some(Watched::new(path));
+    }
+
+    /// Watch a pose library, see `library::load`
+    pub fn watch_poses<P: AsRef<Path>>(&mut self, path: P) {
+        self.poses = Some(Watched::new(path));
+    }
+
+    pub fn detector(&self) -> &Arc<Detector> {
+        &self.detector
+    }
+
+    /// Check the files once and swap in any that changed.
+    /// Files are loaded the first time they're checked.
+    /// Returns None if nothing changed, otherwise an error naming
+    /// any file that was rejected.
+    /// A rejected file isn't tried again until it changes
+    pub fn poll(&mut self) -> Option<io::Result<()>> {
+        let settings = self.settings.as_mut().and_then(|w| {
+            w.load(|contents| {
+                let settings: Settings = serde_json::from_slice(contents).map_err(invalid_data)?;
+                settings.validate().map_err(invalid_data)?;
+                Ok(settings)
+            })
+        });
+        let library = self.poses.as_mut().and_then(|w| {
+            w.load(|contents| {
+                let library = library::read(contents)?;
+                library.validate().map_err(invalid_data)?;
+                Ok(library)
+            })
+        });
+        if settings.is_none() && library.is_none() {
+            return None;
+        }
+        let mut rejected = Vec::new();
+        let settings = settings.and_then(|s| s.map_err(|e| rejected.push(e.to_string())).ok());
+        let library = library.and_then(|l| l.map_err(|e| rejected.push(e.to_string())).ok());
+        self.detector.swap(settings, library);
+        Some(if rejected.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid_data(rejected.join(", ")))
+        })
+    }
+
+    /// Poll every `interval` on a new thread, logging
+    /// reloads and rejected files to stderr
+    pub fn spawn(mut self, interval: Duration) -> Watcher {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let thread = thread::spawn(move || {
+            while !stopped.load(Ordering::SeqCst) {
+                match self.poll() {
+                    Some(Ok(())) => eprintln!("Reloaded detector config"),
+                    Some(Err(e)) => eprintln!("Rejected {}", e),
+                    None => (),
+                }
+                thread::sleep(interval);
+            }
+        });
+        Watcher {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Watcher {
+    /// Stop polling and wait for the thread to finish
+    pub fn stop(mut self) {
+        self.join();
+    }
+
+    fn join(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+impl Watched {
+    fn new<P: AsRef<Path>>(path: P) -> Self {
+        Watched {
+            path: path.as_ref().to_path_buf(),
+            contents: None,
+        }
+    }
+
+    /// Load the file if its contents changed since it was last checked,
+    /// naming it in any error.
+    /// A missing file hasn't changed so the old one is kept
+    fn load<T, F>(&mut self, parse: F) -> Option<io::Result<T>>
+    where
+        F: FnOnce(&[u8]) -> io::Result<T>,
+    {
+        let contents = fs::read(&self.path).ok()?;
+        if self.contents.as_ref() == Some(&contents) {
+            return None;
+        }
+        let loaded =
+            parse(&contents).map_err(|e| invalid_data(format!("{}: {}", self.path.display(), e)));
+        self.contents = Some(contents);
+        Some(loaded)
+    }
+}
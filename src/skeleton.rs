@@ -0,0 +1,246 @@
+//! Skeletons owned by this crate so poses can be detected
+//! without depending on a particular tracker.
+
+use crate::{glm, Vec2};
+use glm::Vec3;
+
+/// Joints a tracker can report.
+/// Ids match Nuitrack's joint numbering
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum JointType {
+    Head = 1,
+    Neck = 2,
+    Torso = 3,
+    Waist = 4,
+    LeftCollar = 5,
+    LeftShoulder = 6,
+    LeftElbow = 7,
+    LeftWrist = 8,
+    LeftHand = 9,
+    LeftFingertip = 10,
+    RightCollar = 11,
+    RightShoulder = 12,
+    RightElbow = 13,
+    RightWrist = 14,
+    RightHand = 15,
+    RightFingertip = 16,
+    LeftHip = 17,
+    LeftKnee = 18,
+    LeftAnkle = 19,
+    LeftFoot = 20,
+    RightHip = 21,
+    RightKnee = 22,
+    RightAnkle = 23,
+    RightFoot = 24,
+}
+
+/// A tracked joint.
+/// With `json` it's written as `{"type": ..., "proj": ..., "real": ..., "confidence": ...}`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Joint {
+    pub joint_type: JointType,
+    /// Position in the image from 0 to 1, with depth in `z`
+    pub proj: Vec3,
+    /// Position in the world, in millimetres
+    pub real: Vec3,
+    pub confidence: f32,
+}
+
+/// The joints of one tracked person
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Skeleton {
+    pub id: i32,
+    pub joints: Vec<Joint>,
+}
+
+/// A joint from any tracker that poses can be detected in
+pub trait JointInput {
+    /// None if this isn't a joint this crate knows
+    fn joint_type(&self) -> Option<JointType>;
+
+    /// Position in the image from 0 to 1
+    fn position(&self) -> Vec2;
+
+    /// Position in the world in millimetres, if the tracker has one.
+    /// Used to check bone lengths, see `Plausibility`
+    fn real(&self) -> Option<Vec3> {
+        None
+    }
+}
+
+impl JointType {
+    /// Every joint in id order
+    pub const ALL: [JointType; 24] = [
+        JointType::Head,
+        JointType::Neck,
+        JointType::Torso,
+        JointType::Waist,
+        JointType::LeftCollar,
+        JointType::LeftShoulder,
+        JointType::LeftElbow,
+        JointType::LeftWrist,
+        JointType::LeftHand,
+        JointType::LeftFingertip,
+        JointType::RightCollar,
+        JointType::RightShoulder,
+        JointType::RightElbow,
+        JointType::RightWrist,
+        JointType::RightHand,
+        JointType::RightFingertip,
+        JointType::LeftHip,
+        JointType::LeftKnee,
+        JointType::LeftAnkle,
+        JointType::LeftFoot,
+        JointType::RightHip,
+        JointType::RightKnee,
+        JointType::RightAnkle,
+        JointType::RightFoot,
+    ];
+
+    /// Joint with this Nuitrack id
+    pub fn from_u32(id: u32) -> Option<Self> {
+        JointType::ALL.iter().cloned().find(|&t| t.id() == id)
+    }
+
+    /// Nuitrack id of this joint
+    pub fn id(&self) -> u32 {
+        *self as u32
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            JointType::Head => "Head",
+            JointType::Neck => "Neck",
+            JointType::Torso => "Torso",
+            JointType::Waist => "Waist",
+            JointType::LeftCollar => "LeftCollar",
+            JointType::LeftShoulder => "LeftShoulder",
+            JointType::LeftElbow => "LeftElbow",
+            JointType::LeftWrist => "LeftWrist",
+            JointType::LeftHand => "LeftHand",
+            JointType::LeftFingertip => "LeftFingertip",
+            JointType::RightCollar => "RightCollar",
+            JointType::RightShoulder => "RightShoulder",
+            JointType::RightElbow => "RightElbow",
+            JointType::RightWrist => "RightWrist",
+            JointType::RightHand => "RightHand",
+            JointType::RightFingertip => "RightFingertip",
+            JointType::LeftHip => "LeftHip",
+            JointType::LeftKnee => "LeftKnee",
+            JointType::LeftAnkle => "LeftAnkle",
+            JointType::LeftFoot => "LeftFoot",
+            JointType::RightHip => "RightHip",
+            JointType::RightKnee => "RightKnee",
+            JointType::RightAnkle => "RightAnkle",
+            JointType::RightFoot => "RightFoot",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        JointType::ALL.iter().cloned().find(|t| t.name() == name)
+    }
+
+    /// The same joint on the other side of the body
+    pub fn mirrored(&self) -> Self {
+        use self::JointType::*;
+        match *self {
+            LeftCollar => RightCollar,
+            LeftShoulder => RightShoulder,
+            LeftElbow => RightElbow,
+            LeftWrist => RightWrist,
+            LeftHand => RightHand,
+            LeftFingertip => RightFingertip,
+            LeftHip => RightHip,
+            LeftKnee => RightKnee,
+            LeftAnkle => RightAnkle,
+            LeftFoot => RightFoot,
+            RightCollar => LeftCollar,
+            RightShoulder => LeftShoulder,
+            RightElbow => LeftElbow,
+            RightWrist => LeftWrist,
+            RightHand => LeftHand,
+            RightFingertip => LeftFingertip,
+            RightHip => LeftHip,
+            RightKnee => LeftKnee,
+            RightAnkle => LeftAnkle,
+            RightFoot => LeftFoot,
+            Head | Neck | Torso | Waist => *self,
+        }
+    }
+}
+
+impl Joint {
+    /// A joint at this image position with full confidence
+    pub fn new(joint_type: JointType, position: Vec2) -> Self {
+        Joint {
+            joint_type,
+            proj: glm::vec3(position.x, position.y, 0.0),
+            real: glm::vec3(0.0, 0.0, 0.0),
+            confidence: 1.0,
+        }
+    }
+}
+
+impl JointInput for Joint {
+    fn joint_type(&self) -> Option<JointType> {
+        Some(self.joint_type)
+    }
+
+    fn position(&self) -> Vec2 {
+        glm::vec2(self.proj.x, self.proj.y)
+    }
+
+    /// None for joints made with `Joint::new`, which are at the origin
+    fn real(&self) -> Option<Vec3> {
+        if self.real == glm::vec3(0.0, 0.0, 0.0) {
+            None
+        } else {
+            Some(self.real)
+        }
+    }
+}
+
+/// How far past the wrist Nuitrack puts the hand as a
+/// fraction of the forearm, averaged over the default poses
+const HAND_EXTENSION: f32 = 0.21;
+
+/// A Nuitrack style hand for trackers that stop at the wrist,
+/// found by carrying on along the forearm
+fn hand_from_forearm(hand: JointType, elbow: &Joint, wrist: &Joint) -> Joint {
+    Joint {
+        joint_type: hand,
+        proj: wrist.proj + (wrist.proj - elbow.proj) * HAND_EXTENSION,
+        real: wrist.real + (wrist.real - elbow.real) * HAND_EXTENSION,
+        confidence: elbow.confidence.min(wrist.confidence),
+    }
+}
+
+/// Add hands carried on from the forearms to
+/// joints that have elbows and wrists but no hands
+pub(crate) fn add_hands(joints: &mut Vec<Joint>) {
+    let arms = [
+        (
+            JointType::LeftHand,
+            JointType::LeftElbow,
+            JointType::LeftWrist,
+        ),
+        (
+            JointType::RightHand,
+            JointType::RightElbow,
+            JointType::RightWrist,
+        ),
+    ];
+    for &(hand, elbow, wrist) in arms.iter() {
+        let find = |t| joints.iter().find(|j: &&Joint| j.joint_type == t);
+        if find(hand).is_some() {
+            continue;
+        }
+        let found = match (find(elbow), find(wrist)) {
+            (Some(e), Some(w)) => Some(hand_from_forearm(hand, e, w)),
+            _ => None,
+        };
+        joints.extend(found);
+    }
+}
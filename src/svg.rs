@@ -0,0 +1,172 @@
+//! Draws detection reports as SVG so alignments can be checked by eye.
+//! The skeleton is drawn in black and the template in blue, both after
+//! alignment. Joints are coloured from green to red by how close they
+//! are to `joint_cutoff`.
+
+use crate::{PoseReport, Report, Vec2};
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+const PANEL_SIZE: f32 = 400.0;
+const MARGIN: f32 = 40.0;
+const COLUMNS: usize = 3;
+
+/// Lines between the joints in `ARMS` order
+const BONES: [(usize, usize); 7] = [(0, 1), (1, 2), (2, 3), (4, 5), (5, 6), (6, 7), (0, 4)];
+
+/// Render every pose in a report, one panel each
+pub fn render_report(report: &Report) -> String {
+    let columns = report.poses.len().clamp(1, COLUMNS);
+    let rows = report.poses.len().div_ceil(COLUMNS).max(1);
+    let width = columns as f32 * PANEL_SIZE;
+    let height = rows as f32 * PANEL_SIZE;
+
+    let mut svg = header(width, height);
+    for (i, pose) in report.poses.iter().enumerate() {
+        let x = (i % COLUMNS) as f32 * PANEL_SIZE;
+        let y = (i / COLUMNS) as f32 * PANEL_SIZE;
+        panel(&mut svg, pose, x, y, report.pose == Some(pose.pose));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Render a single pose
+pub fn render_pose(pose: &PoseReport) -> String {
+    let mut svg = header(PANEL_SIZE, PANEL_SIZE);
+    panel(&mut svg, pose, 0.0, 0.0, pose.failure.is_none());
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Render a report to a file
+pub fn write_report<P: AsRef<Path>>(report: &Report, path: P) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(render_report(report).as_bytes())
+}
+
+fn header(width: f32, height: f32) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\">\n\
+         <rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>\n",
+        w = width,
+        h = height
+    )
+}
+
+fn panel(svg: &mut String, pose: &PoseReport, x: f32, y: f32, matched: bool) {
+    let border = if matched { "#2a2" } else { "#ccc" };
+    writeln!(
+        svg,
+        "<g transform=\"translate({} {})\">\n\
+         <rect x=\"1\" y=\"1\" width=\"{s}\" height=\"{s}\" fill=\"none\" stroke=\"{}\"/>",
+        x,
+        y,
+        border,
+        s = PANEL_SIZE - 2.0
+    )
+    .unwrap();
+
+    text(svg, 10.0, 20.0, &format!("{:?}", pose.pose));
+    if let Some(rotation) = pose.rotation {
+        text(
+            svg,
+            10.0,
+            36.0,
+            &format!(
+                "rotation {:.3} rad ({:.1}°)",
+                rotation,
+                rotation.to_degrees()
+            ),
+        );
+    }
+    if let Some(scale) = pose.scale {
+        text(svg, 10.0, 52.0, &format!("scale {:.3}", scale));
+    }
+    match pose.failure {
+        Some(ref failure) => text(svg, 10.0, PANEL_SIZE - 10.0, &format!("{:?}", failure)),
+        None => text(svg, 10.0, PANEL_SIZE - 10.0, "Matched"),
+    }
+
+    if !pose.skeleton.is_empty() && !pose.template.is_empty() {
+        let extent = pose
+            .skeleton
+            .iter()
+            .chain(pose.template.iter())
+            .map(|v| v.x.abs().max(v.y.abs()))
+            .fold(f32::EPSILON, f32::max);
+        let scale = (PANEL_SIZE / 2.0 - MARGIN) / extent;
+        let to_panel = |v: &Vec2| {
+            (
+                PANEL_SIZE / 2.0 + v.x * scale,
+                PANEL_SIZE / 2.0 + v.y * scale,
+            )
+        };
+
+        figure(svg, &pose.template, &to_panel, "#36c", "6 4");
+        figure(svg, &pose.skeleton, &to_panel, "#222", "none");
+
+        for (n, v) in pose.skeleton.iter().enumerate() {
+            let (px, py) = to_panel(v);
+            let colour = pose
+                .joints
+                .get(n)
+                .map(|jd| joint_colour(jd.distance / jd.cutoff))
+                .unwrap_or_else(|| "#888".to_string());
+            writeln!(
+                svg,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"6\" fill=\"{}\" stroke=\"#222\"/>",
+                px, py, colour
+            )
+            .unwrap();
+        }
+    }
+    svg.push_str("</g>\n");
+}
+
+fn figure<F>(svg: &mut String, points: &[Vec2], to_panel: &F, colour: &str, dash: &str)
+where
+    F: Fn(&Vec2) -> (f32, f32),
+{
+    for &(a, b) in BONES.iter() {
+        if let (Some(a), Some(b)) = (points.get(a), points.get(b)) {
+            let (x1, y1) = to_panel(a);
+            let (x2, y2) = to_panel(b);
+            writeln!(
+                svg,
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" \
+                 stroke=\"{}\" stroke-width=\"3\" stroke-dasharray=\"{}\"/>",
+                x1, y1, x2, y2, colour, dash
+            )
+            .unwrap();
+        }
+    }
+}
+
+fn text(svg: &mut String, x: f32, y: f32, content: &str) {
+    writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"13\">{}</text>",
+        x, y, content
+    )
+    .unwrap();
+}
+
+/// Green when the joint is on the template, through yellow,
+/// to red at the cutoff and beyond
+fn joint_colour(ratio: f32) -> String {
+    let ratio = if ratio.is_finite() {
+        ratio.clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let (r, g) = if ratio < 0.5 {
+        ((ratio * 2.0 * 255.0) as u8, 200)
+    } else {
+        (255, ((1.0 - ratio) * 2.0 * 200.0) as u8)
+    };
+    format!("#{:02x}{:02x}00", r, g)
+}
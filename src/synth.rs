@@ -0,0 +1,159 @@
+//! Generate skeletons from a template for tests and augmentation.
+//!
+//! A template is rotated and scaled about its centre, translated,
+//! then has noise added to each joint. Joints can be dropped and
+//! joints that land outside the frame can be clipped like the
+//! sensor does.
+
+use crate::{glm, Joint, JointPos, JointType, Skeleton, Vec2};
+
+/// How to change the template
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Distortion {
+    /// Radians about the template's centre,
+    /// clockwise in the image when positive
+    pub rotation: f32,
+    /// About the template's centre
+    pub scale: f32,
+    pub translation: Vec2,
+    /// Standard deviation of the Gaussian noise added to each joint
+    pub noise: f32,
+    /// Joints to leave out
+    pub dropped: Vec<JointType>,
+    /// Chance of leaving out any other joint
+    pub drop_chance: f32,
+    pub clipping: Clipping,
+}
+
+/// What happens to joints outside the frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum Clipping {
+    /// Leave them where they are
+    Keep,
+    /// Leave them out of the skeleton
+    Drop,
+    /// Move them to the edge of the frame
+    Clamp,
+}
+
+/// Seeded generator of skeletons.
+/// The same seed always gives the same skeletons
+pub struct Generator {
+    state: u64,
+    next_id: i32,
+}
+
+impl Default for Distortion {
+    fn default() -> Self {
+        Distortion {
+            rotation: 0.0,
+            scale: 1.0,
+            translation: glm::vec2(0.0, 0.0),
+            noise: 0.0,
+            dropped: Vec::new(),
+            drop_chance: 0.0,
+            clipping: Clipping::Keep,
+        }
+    }
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Generator {
+            // xorshift can't start from zero
+            state: (seed ^ 0x9E37_79B9_7F4A_7C15) | 1,
+            next_id: 1,
+        }
+    }
+
+    /// Move the template's joints
+    pub fn distort(&mut self, template: &JointPos, distortion: &Distortion) -> JointPos {
+        let count = template.len().max(1) as f32;
+        let centre = template.values().fold(glm::vec2(0.0, 0.0), |c, v| c + v) / count;
+        let (sin, cos) = distortion.rotation.sin_cos();
+
+        let mut joints: Vec<(JointType, Vec2)> = template.iter().map(|(&t, &v)| (t, v)).collect();
+        // HashMap order isn't stable so sort to keep seeds reproducible
+        joints.sort_by_key(|&(t, _)| t);
+
+        let mut distorted = JointPos::new();
+        for (t, v) in joints {
+            if distortion.dropped.contains(&t) {
+                continue;
+            }
+            if distortion.drop_chance > 0.0 && self.uniform(0.0, 1.0) < distortion.drop_chance {
+                continue;
+            }
+            let d = v - centre;
+            let rotated = glm::vec2(d.x * cos - d.y * sin, d.x * sin + d.y * cos);
+            let mut v = centre + distortion.translation + rotated * distortion.scale;
+            if distortion.noise > 0.0 {
+                v.x += self.gaussian() * distortion.noise;
+                v.y += self.gaussian() * distortion.noise;
+            }
+            let outside = v.x < 0.0 || v.x > 1.0 || v.y < 0.0 || v.y > 1.0;
+            match distortion.clipping {
+                Clipping::Drop if outside => continue,
+                Clipping::Clamp => v = glm::clamp(&v, 0.0, 1.0),
+                _ => (),
+            }
+            distorted.insert(t, v);
+        }
+        distorted
+    }
+
+    /// Make a skeleton ready for `Detector::detect`
+    pub fn skeleton(&mut self, template: &JointPos, distortion: &Distortion) -> Skeleton {
+        let mut joints: Vec<Joint> = self
+            .distort(template, distortion)
+            .into_iter()
+            .map(|(t, v)| Joint::new(t, v))
+            .collect();
+        joints.sort_by_key(|j| j.joint_type);
+        let id = self.next_id;
+        self.next_id += 1;
+        Skeleton { id, joints }
+    }
+
+    /// Uniform random number in `[low, high)`
+    pub fn uniform(&mut self, low: f32, high: f32) -> f32 {
+        // Top 24 bits fill an f32 mantissa exactly
+        let unit = (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+        low + (high - low) * unit
+    }
+
+    /// Standard normal random number
+    pub fn gaussian(&mut self) -> f32 {
+        // Box-Muller
+        let u1 = self.uniform(f32::EPSILON, 1.0);
+        let u2 = self.uniform(0.0, 1.0);
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
+    }
+
+    /// A distortion picked uniformly within these limits.
+    /// Rotation and translation are picked from `-limit..limit`
+    /// and scale from `1/scale..scale`. Everything else is copied.
+    pub fn random_distortion(&mut self, limits: &Distortion) -> Distortion {
+        let scale = limits.scale.max(1.0);
+        Distortion {
+            rotation: self.uniform(-limits.rotation, limits.rotation),
+            scale: self.uniform(1.0 / scale, scale),
+            translation: glm::vec2(
+                self.uniform(-limits.translation.x, limits.translation.x),
+                self.uniform(-limits.translation.y, limits.translation.y),
+            ),
+            noise: limits.noise,
+            ..limits.clone()
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        // xorshift64*
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
@@ -0,0 +1,101 @@
+//! Turn detections each frame into events when users
+//! come and go and start or stop holding a pose.
+
+use crate::Pose;
+use std::collections::HashMap;
+
+/// Something that changed since the last frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    /// A user was seen for the first time
+    UserFound(i32),
+    /// A user wasn't in this frame
+    UserLost(i32),
+    /// A user started holding a pose
+    PoseStarted(i32, Pose),
+    /// A user stopped holding a pose
+    PoseEnded(i32, Pose),
+}
+
+/// Remembers each user's pose between frames
+#[derive(Clone, Debug, Default)]
+pub struct Tracker {
+    users: HashMap<i32, Option<Pose>>,
+}
+
+impl Event {
+    pub fn user(&self) -> i32 {
+        match *self {
+            Event::UserFound(user)
+            | Event::UserLost(user)
+            | Event::PoseStarted(user, _)
+            | Event::PoseEnded(user, _) => user,
+        }
+    }
+
+    pub fn pose(&self) -> Option<Pose> {
+        match *self {
+            Event::PoseStarted(_, pose) | Event::PoseEnded(_, pose) => Some(pose),
+            _ => None,
+        }
+    }
+
+    /// Short name for addresses and messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::UserFound(_) => "found",
+            Event::UserLost(_) => "lost",
+            Event::PoseStarted(..) => "started",
+            Event::PoseEnded(..) => "ended",
+        }
+    }
+}
+
+impl Tracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update with every user's detection in this frame.
+    /// Users missing from the frame are lost.
+    /// Returns what changed, ordered by user
+    pub fn update<I>(&mut self, detections: I) -> Vec<Event>
+    where
+        I: IntoIterator<Item = (i32, Option<Pose>)>,
+    {
+        let mut seen: HashMap<i32, Option<Pose>> = detections.into_iter().collect();
+        let mut users: Vec<i32> = seen.keys().chain(self.users.keys()).cloned().collect();
+        users.sort();
+        users.dedup();
+
+        let mut events = Vec::new();
+        for user in users {
+            let before = self.users.get(&user).cloned();
+            let now = seen.remove(&user);
+            if before.is_none() {
+                events.push(Event::UserFound(user));
+            }
+            let (old, new) = (before.and_then(|p| p), now.and_then(|p| p));
+            if old != new {
+                events.extend(old.map(|pose| Event::PoseEnded(user, pose)));
+                events.extend(new.map(|pose| Event::PoseStarted(user, pose)));
+            }
+            match now {
+                Some(pose) => {
+                    self.users.insert(user, pose);
+                }
+                None => {
+                    self.users.remove(&user);
+                    events.push(Event::UserLost(user));
+                }
+            }
+        }
+        events
+    }
+
+    /// The pose each user is holding
+    pub fn poses<'a>(&'a self) -> impl Iterator<Item = (i32, Option<Pose>)> + 'a {
+        self.users.iter().map(|(&user, &pose)| (user, pose))
+    }
+}
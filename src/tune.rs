@@ -0,0 +1,177 @@
+//! Search for the cutoffs that do best on labelled skeletons.
+//!
+//! Each frame is aligned against every pose once, then the
+//! cutoffs are searched over a grid without realigning.
+//! Poses that failed for reasons the cutoffs don't change,
+//! like a missing joint or tilted shoulders, are never matched.
+
+use crate::evaluate::{Evaluation, Frame};
+use crate::{Cutoffs, Detector, Pose, Settings};
+use std::cmp::Ordering::Equal;
+
+/// What the tuner tries to maximise
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum Objective {
+    /// Mean F1 over the labelled poses
+    MacroF1,
+    /// Mean F1, skipping cutoffs that detect a pose in
+    /// more than this fraction of "none" frames
+    MacroF1WithFalsePositiveCap(f32),
+}
+
+/// Grid search over joint and rotation cutoffs
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tuner {
+    pub joint_cutoffs: Vec<f32>,
+    pub rotation_cutoffs: Vec<f32>,
+    pub objective: Objective,
+    /// After finding global cutoffs, search
+    /// overrides for each pose as well
+    pub per_pose: bool,
+}
+
+/// The best settings found
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tuned {
+    pub settings: Settings,
+    pub evaluation: Evaluation,
+    pub score: f32,
+}
+
+/// How each pose compared on a single frame
+struct Candidates {
+    label: Option<Pose>,
+    /// Pose, rotation and furthest joint for each aligned pose,
+    /// with both sides of poses that can match either hand
+    poses: Vec<(Pose, f32, f32)>,
+}
+
+impl Default for Tuner {
+    fn default() -> Self {
+        Tuner {
+            joint_cutoffs: steps(0.02, 0.3, 15),
+            rotation_cutoffs: steps(0.05, 1.0, 20),
+            objective: Objective::MacroF1,
+            per_pose: false,
+        }
+    }
+}
+
+/// `count` evenly spaced values from `start` to `end` inclusive
+pub fn steps(start: f32, end: f32, count: usize) -> Vec<f32> {
+    match count {
+        0 => Vec::new(),
+        1 => vec![start],
+        _ => (0..count)
+            .map(|i| start + (end - start) * i as f32 / (count - 1) as f32)
+            .collect(),
+    }
+}
+
+impl Objective {
+    /// Score an evaluation, higher is better.
+    /// None if it breaks a constraint
+    pub fn score(&self, evaluation: &Evaluation) -> Option<f32> {
+        match *self {
+            Objective::MacroF1 => Some(evaluation.macro_f1()),
+            Objective::MacroF1WithFalsePositiveCap(cap) => {
+                if evaluation.false_positive_rate <= cap {
+                    Some(evaluation.macro_f1())
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+impl Tuner {
+    /// Find the settings that score best on these frames using the
+    /// detector's poses. The detector's other settings are kept.
+    /// Returns None if no cutoffs on the grid meet the objective
+    pub fn tune(&self, detector: &Detector, frames: &[Frame]) -> Option<Tuned> {
+        let candidates: Vec<Candidates> = frames
+            .iter()
+            .map(|frame| Candidates {
+                label: frame.label,
+                poses: detector
+                    .check_sides(&frame.joints)
+                    .iter()
+                    .filter(|p| p.failure.map(|f| f.is_cutoff()).unwrap_or(true))
+                    .filter_map(|p| Some((p.pose, p.rotation?, p.furthest()?)))
+                    .collect(),
+            })
+            .collect();
+
+        let mut base = detector.settings();
+        base.overrides.clear();
+
+        let mut best: Option<Tuned> = None;
+        for cutoffs in self.grid() {
+            let settings = Settings {
+                joint_cutoff: cutoffs.joint_cutoff,
+                rotation_cutoff: cutoffs.rotation_cutoff,
+                ..base.clone()
+            };
+            best = self.better(best, settings, &candidates);
+        }
+
+        if self.per_pose {
+            let poses = detector.poses();
+            for &pose in Pose::ALL.iter().filter(|p| poses.contains_key(p)) {
+                for cutoffs in self.grid() {
+                    let mut settings = match best {
+                        Some(ref b) => b.settings.clone(),
+                        None => base.clone(),
+                    };
+                    settings.overrides.insert(pose, cutoffs);
+                    best = self.better(best, settings, &candidates);
+                }
+            }
+        }
+        best
+    }
+
+    fn grid<'a>(&'a self) -> impl Iterator<Item = Cutoffs> + 'a {
+        self.joint_cutoffs.iter().flat_map(move |&joint_cutoff| {
+            self.rotation_cutoffs
+                .iter()
+                .map(move |&rotation_cutoff| Cutoffs {
+                    joint_cutoff,
+                    rotation_cutoff,
+                })
+        })
+    }
+
+    /// Keep whichever of `best` and `settings` scores higher
+    fn better(
+        &self,
+        best: Option<Tuned>,
+        settings: Settings,
+        candidates: &[Candidates],
+    ) -> Option<Tuned> {
+        let evaluation =
+            Evaluation::from_detections(candidates.iter().map(|c| (c.label, detect(c, &settings))));
+        match self.objective.score(&evaluation) {
+            Some(score) if best.as_ref().map(|b| score > b.score).unwrap_or(true) => Some(Tuned {
+                settings,
+                evaluation,
+                score,
+            }),
+            _ => best,
+        }
+    }
+}
+
+/// Same choice `Detector::detect` makes: the closest pose within its cutoffs
+fn detect(candidates: &Candidates, settings: &Settings) -> Option<Pose> {
+    candidates
+        .poses
+        .iter()
+        .filter(|&&(pose, rotation, furthest)| settings.accepts(pose, rotation, furthest))
+        .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(Equal))
+        .map(|&(pose, _, _)| pose)
+}
@@ -0,0 +1,299 @@
+//! Stream detections and events to browsers over WebSockets.
+//!
+//! Every frame is broadcast to all clients as a text message:
+//!
+//! ```json
+//! {"type": "frame",
+//!  "detections": [{"user": 1, "pose": "DabR", "chirality": "Same",
+//!                  "scores": {"DabR": 0.93, "Roof": 0.0, ...}}],
+//!  "events": [{"event": "started", "user": 1, "pose": "DabR"}]}
+//! ```
+//!
+//! `chirality` is `Mirrored` when a pose in `Settings::either_hand`
+//! matched its mirror image, and is left out if no pose matched.
+//! Skeletons rejected by `Settings::plausibility` have no scores
+//! and a `rejected` reason such as `"HandsSwapped"`.
+//!
+//! Clients can send control messages. Each one is answered with
+//! `{"type": "ok"}` or `{"type": "error", "message": "..."}`:
+//!
+//! ```json
+//! {"type": "settings", "joint_cutoff": 0.1, "rotation_cutoff": 0.3}
+//! {"type": "poses", "library": {"DabR": {"LeftShoulder": [0.68, 0.49], ...}}}
+//! ```
+//!
+//! Libraries are only accepted inline so clients
+//! can't make the server read its own files.
+//!
+//! The protocol is handled by `tungstenite` and connections are
+//! plain `ws://`. Each client has its own thread and a short queue of
+//! frames, and a client that falls behind is disconnected rather than
+//! holding up the others.
+
+use crate::library;
+use crate::tracker::{Event, Tracker};
+use crate::{invalid_data, Chirality, Detector, Implausible, Library, Skeleton};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tungstenite::protocol::WebSocketConfig;
+use tungstenite::HandshakeError;
+use tungstenite::{Message, WebSocket};
+
+/// Accepts clients in the background and broadcasts frames to them
+pub struct Server {
+    detector: Arc<Detector>,
+    clients: Arc<Mutex<Vec<Client>>>,
+    tracker: Tracker,
+    address: SocketAddr,
+}
+
+/// Queue of frames for a client's thread to send
+type Client = SyncSender<String>;
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Outgoing<'a> {
+    Frame {
+        detections: Vec<Detection>,
+        events: Vec<EventRecord>,
+    },
+    Ok,
+    Error {
+        message: &'a str,
+    },
+}
+
+#[derive(Serialize)]
+struct Detection {
+    user: i32,
+    pose: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chirality: Option<Chirality>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rejected: Option<Implausible>,
+    scores: BTreeMap<&'static str, f32>,
+}
+
+#[derive(Serialize)]
+struct EventRecord {
+    event: &'static str,
+    user: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pose: Option<&'static str>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Control {
+    Settings {
+        joint_cutoff: Option<f32>,
+        rotation_cutoff: Option<f32>,
+    },
+    Poses {
+        library: serde_json::Value,
+    },
+}
+
+/// Largest message accepted from a client
+const MAX_MESSAGE: usize = 1 << 20;
+
+/// Frames waiting for a client before it's dropped for falling behind
+const QUEUE: usize = 16;
+
+/// How long a client's thread waits for a message
+/// before checking for frames to send
+const POLL: Duration = Duration::from_millis(10);
+
+/// How long a write can block before the client is dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+impl Server {
+    /// Listen for clients on this address.
+    /// Control messages change this detector
+    pub fn bind<A: ToSocketAddrs>(address: A, detector: Arc<Detector>) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+
+        let accept_clients = clients.clone();
+        let accept_detector = detector.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let clients = accept_clients.clone();
+                let detector = accept_detector.clone();
+                if let Ok(stream) = stream {
+                    thread::spawn(move || {
+                        // A client that fails the handshake or
+                        // disconnects is simply forgotten
+                        let _ = serve(stream, &clients, &detector);
+                    });
+                }
+            }
+        });
+
+        Ok(Server {
+            detector,
+            clients,
+            tracker: Tracker::new(),
+            address,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn detector(&self) -> &Arc<Detector> {
+        &self.detector
+    }
+
+    /// Detect poses in a frame of skeletons and broadcast the
+    /// detections and any events. Returns the events
+    pub fn send_frame(&mut self, skeletons: &[Skeleton]) -> Vec<Event> {
+        let mut detections = Vec::with_capacity(skeletons.len());
+        let mut poses = Vec::with_capacity(skeletons.len());
+        for skeleton in skeletons {
+            let report = self.detector.detect_explain(&skeleton.joints);
+            poses.push((skeleton.id, report.pose));
+            detections.push(Detection {
+                user: skeleton.id,
+                pose: report.pose.map(|p| p.name()),
+                chirality: report.chirality(),
+                rejected: report.rejected,
+                scores: report
+                    .poses
+                    .iter()
+                    .map(|p| (p.pose.name(), p.score()))
+                    .collect(),
+            });
+        }
+        let events = self.tracker.update(poses);
+        let message = Outgoing::Frame {
+            detections,
+            events: events
+                .iter()
+                .map(|e| EventRecord {
+                    event: e.name(),
+                    user: e.user(),
+                    pose: e.pose().map(|p| p.name()),
+                })
+                .collect(),
+        };
+        self.broadcast(&message);
+        events
+    }
+
+    /// Queue for every client without waiting on any of them,
+    /// dropping clients that have left or can't keep up
+    fn broadcast(&self, message: &Outgoing) {
+        let text = serde_json::to_string(message).expect("Messages always serialize");
+        let mut clients = self.clients.lock().expect("Clients lock poisoned");
+        clients.retain(|client| client.try_send(text.clone()).is_ok());
+    }
+}
+
+/// Handshake then send frames and answer control
+/// messages until the client leaves or is dropped
+fn serve(
+    stream: TcpStream,
+    clients: &Mutex<Vec<Client>>,
+    detector: &Detector,
+) -> tungstenite::Result<()> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    // Queue frames from before the handshake is
+    // answered so the client doesn't miss any
+    let (client, frames) = mpsc::sync_channel(QUEUE);
+    clients.lock().expect("Clients lock poisoned").push(client);
+    let config = WebSocketConfig {
+        max_message_size: Some(MAX_MESSAGE),
+        max_frame_size: Some(MAX_MESSAGE),
+        ..WebSocketConfig::default()
+    };
+    let mut socket =
+        tungstenite::server::accept_with_config(stream, Some(config)).map_err(|e| match e {
+            HandshakeError::Failure(e) => e,
+            HandshakeError::Interrupted(_) => io::Error::from(io::ErrorKind::WouldBlock).into(),
+        })?;
+    socket.get_ref().set_read_timeout(Some(POLL))?;
+
+    loop {
+        if !send_frames(&mut socket, &frames)? {
+            socket.close(None)?;
+            return socket.write_pending();
+        }
+        match socket.read_message() {
+            Ok(Message::Text(text)) => {
+                socket.write_message(Message::Text(respond(&text, detector)))?
+            }
+            // Pings and closes are answered by tungstenite
+            Ok(_) => (),
+            Err(tungstenite::Error::Io(ref e)) if timed_out(e) => (),
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Send the frames queued for this client.
+/// False if it was dropped from the broadcast
+fn send_frames(
+    socket: &mut WebSocket<TcpStream>,
+    frames: &Receiver<String>,
+) -> tungstenite::Result<bool> {
+    loop {
+        match frames.try_recv() {
+            Ok(text) => socket.write_message(Message::Text(text))?,
+            Err(TryRecvError::Empty) => return Ok(true),
+            Err(TryRecvError::Disconnected) => return Ok(false),
+        }
+    }
+}
+
+fn timed_out(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut
+}
+
+/// Apply a control message and say how it went
+fn respond(text: &str, detector: &Detector) -> String {
+    let response = match control(text, detector) {
+        Ok(()) => serde_json::to_string(&Outgoing::Ok),
+        Err(e) => serde_json::to_string(&Outgoing::Error {
+            message: &e.to_string(),
+        }),
+    };
+    response.expect("Messages always serialize")
+}
+
+/// Apply a control message to the detector
+fn control(text: &str, detector: &Detector) -> io::Result<()> {
+    match serde_json::from_str(text).map_err(invalid_data)? {
+        Control::Settings {
+            joint_cutoff,
+            rotation_cutoff,
+        } => detector
+            .try_update_settings(|settings| {
+                if let Some(c) = joint_cutoff {
+                    settings.joint_cutoff = c;
+                }
+                if let Some(c) = rotation_cutoff {
+                    settings.rotation_cutoff = c;
+                }
+            })
+            .map_err(invalid_data),
+        Control::Poses { library } => {
+            set_library(library::from_str(&library.to_string())?, detector)
+        }
+    }
+}
+
+fn set_library(library: Library, detector: &Detector) -> io::Result<()> {
+    library.validate().map_err(invalid_data)?;
+    detector.set_library(library);
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use nalgebra_glm as glm;
+use nuitrack_pose_estimation as pe;
+
+use pe::bvh::{self, Bvh, Camera};
+use pe::JointType;
+
+const ARMS: &str = "HIERARCHY
+ROOT Hips
+{
+    OFFSET 0 0 0
+    CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    JOINT mixamorig:LeftArm
+    {
+        OFFSET 20 50 0
+        CHANNELS 3 Zrotation Xrotation Yrotation
+        JOINT LeftForeArm
+        {
+            OFFSET 30 0 0
+            CHANNELS 3 Zrotation Xrotation Yrotation
+            JOINT LeftHand
+            {
+                OFFSET 25 0 0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                End Site
+                {
+                    OFFSET 10 0 0
+                }
+            }
+        }
+    }
+    JOINT RightArm
+    {
+        OFFSET -20 50 0
+        CHANNELS 3 Zrotation Xrotation Yrotation
+        JOINT RightForeArm
+        {
+            OFFSET -30 0 0
+            CHANNELS 3 Zrotation Xrotation Yrotation
+            JOINT RightHand
+            {
+                OFFSET -25 0 0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+            }
+        }
+    }
+}
+MOTION
+Frames: 2
+Frame Time: 0.0333333
+0 0 0 0 0 0  0 0 0  0 0 0  0 0 0  0 0 0  0 0 0  0 0 0
+5 0 0 0 0 0  90 0 0  0 0 0  0 0 0  0 0 0  0 0 0  0 0 0
+";
+
+const LEGS: &str = "HIERARCHY
+ROOT Hips
+{
+    OFFSET 0 0 0
+    CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+    JOINT LeftUpLeg
+    {
+        OFFSET 10 0 0
+        CHANNELS 3 Zrotation Xrotation Yrotation
+        JOINT LeftLeg
+        {
+            OFFSET 0 -45 0
+            CHANNELS 3 Zrotation Xrotation Yrotation
+            JOINT LeftFoot
+            {
+                OFFSET 0 -40 0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                JOINT LeftToeBase
+                {
+                    OFFSET 0 -5 15
+                    CHANNELS 3 Zrotation Xrotation Yrotation
+                }
+            }
+        }
+    }
+    JOINT RightUpLeg
+    {
+        OFFSET -10 0 0
+        CHANNELS 3 Zrotation Xrotation Yrotation
+        JOINT RightLeg
+        {
+            OFFSET 0 -45 0
+            CHANNELS 3 Zrotation Xrotation Yrotation
+            JOINT RightFoot
+            {
+                OFFSET 0 -40 0
+                CHANNELS 3 Zrotation Xrotation Yrotation
+                JOINT RightToeBase
+                {
+                    OFFSET 0 -5 15
+                    CHANNELS 3 Zrotation Xrotation Yrotation
+                }
+            }
+        }
+    }
+}
+MOTION
+Frames: 1
+Frame Time: 0.0333333
+0 0 0 0 0 0  0 0 0  0 0 0  0 0 0  0 0 0  0 0 0  0 0 0  0 0 0  0 0 0
+";
+
+#[test]
+fn forward_kinematics() {
+    let bvh = Bvh::parse(ARMS).unwrap();
+    assert_eq!(bvh.frame_count(), 2);
+    assert_eq!(bvh.joint_names().count(), 8);
+
+    let rest = bvh.world_positions(0).unwrap();
+    assert!(glm::distance(&rest["LeftHand"], &glm::vec3(75.0, 50.0, 0.0)) < 0.001);
+    assert!(glm::distance(&rest["LeftHandEnd"], &glm::vec3(85.0, 50.0, 0.0)) < 0.001);
+
+    // Root moved along x and left arm raised straight up
+    let raised = bvh.world_positions(1).unwrap();
+    assert!(glm::distance(&raised["LeftForeArm"], &glm::vec3(25.0, 80.0, 0.0)) < 0.001);
+    assert!(glm::distance(&raised["LeftHandEnd"], &glm::vec3(25.0, 115.0, 0.0)) < 0.001);
+    assert!(glm::distance(&raised["RightHand"], &glm::vec3(-70.0, 50.0, 0.0)) < 0.001);
+
+    assert!(bvh.world_positions(2).is_none());
+}
+
+#[test]
+fn camera_projection() {
+    let camera = Camera {
+        position: glm::vec3(0.0, 0.0, 10.0),
+        target: glm::vec3(0.0, 0.0, 0.0),
+        up: glm::vec3(0.0, 1.0, 0.0),
+        fov: std::f32::consts::FRAC_PI_2,
+        aspect: 1.0,
+    };
+    let centre = camera.project(&glm::vec3(0.0, 0.0, 0.0)).unwrap();
+    assert!(glm::distance(&centre, &glm::vec2(0.5, 0.5)) < 0.0001);
+    let right = camera.project(&glm::vec3(10.0, 0.0, 0.0)).unwrap();
+    assert!(glm::distance(&right, &glm::vec2(1.0, 0.5)) < 0.0001);
+    let up = camera.project(&glm::vec3(0.0, 5.0, 0.0)).unwrap();
+    assert!(glm::distance(&up, &glm::vec2(0.5, 0.25)) < 0.0001);
+    assert!(camera.project(&glm::vec3(0.0, 0.0, 20.0)).is_none());
+}
+
+#[test]
+fn template_from_frame() {
+    let bvh = Bvh::parse(ARMS).unwrap();
+    let positions = bvh.world_positions(1).unwrap();
+    let camera = Camera::framing(positions.values(), 4.0 / 3.0);
+    let template = bvh.template(1, &camera, &bvh::NAMES).unwrap();
+
+    // Arms, waist and hands carried on from the wrists
+    assert_eq!(template.len(), 9);
+    assert!(template
+        .values()
+        .all(|v| v.x > 0.0 && v.x < 1.0 && v.y > 0.0 && v.y < 1.0));
+    let left = template[&JointType::LeftShoulder];
+    let right = template[&JointType::RightShoulder];
+    // Facing the camera so their left is on the right of the image
+    assert!(left.x > right.x);
+    let hand = template[&JointType::LeftHand];
+    let wrist = template[&JointType::LeftWrist];
+    assert!(hand.y < wrist.y);
+    assert!((hand.x - wrist.x).abs() < 0.0001);
+}
+
+#[test]
+fn template_with_feet() {
+    let bvh = Bvh::parse(LEGS).unwrap();
+    let positions = bvh.world_positions(0).unwrap();
+    let camera = Camera::framing(positions.values(), 1.0);
+    let template = bvh.template(0, &camera, &bvh::NAMES).unwrap();
+
+    for &(ankle, foot) in [
+        (JointType::LeftAnkle, JointType::LeftFoot),
+        (JointType::RightAnkle, JointType::RightFoot),
+    ]
+    .iter()
+    {
+        let ankle = template[&ankle];
+        let foot = template[&foot];
+        // Toes point at the camera so sit a little lower in the image
+        assert!(foot.y > ankle.y, "{:?} {:?}", ankle, foot);
+    }
+    let left = template[&JointType::LeftFoot];
+    let right = template[&JointType::RightFoot];
+    assert!(left.x > right.x);
+}
+
+#[test]
+fn reject_bad_files() {
+    assert!(Bvh::parse(&ARMS.replace(
+        "Zrotation Xrotation Yrotation\n        JOINT LeftForeArm",
+        "Wrotation Xrotation Yrotation\n        JOINT LeftForeArm"
+    ))
+    .is_err());
+    let truncated = &ARMS[..ARMS.len() - 10];
+    assert!(Bvh::parse(truncated).is_err());
+    assert!(Bvh::parse("HIERARCHY\nROOT Hips\n{\n").is_err());
+}
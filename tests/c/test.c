@@ -0,0 +1,113 @@
+/* Checks the C interface. Run with `make -C tests/c` */
+
+#include "nuitrack_pose_estimation.h"
+
+#include <stdio.h>
+#include <string.h>
+
+static int failures = 0;
+
+#define CHECK(cond)                                                    \
+    do {                                                               \
+        if (!(cond)) {                                                 \
+            fprintf(stderr, "%s:%d: %s failed\n", __FILE__, __LINE__, #cond); \
+            failures++;                                                \
+        }                                                              \
+    } while (0)
+
+/* The default DabR template */
+static const npe_joint DAB_R[] = {
+    {NPE_JOINT_LEFT_SHOULDER, 0.68835175f, 0.49775392f, 1.0f},
+    {NPE_JOINT_LEFT_ELBOW, 0.5864927f, 0.5303629f, 1.0f},
+    {NPE_JOINT_LEFT_WRIST, 0.45776764f, 0.40316057f, 1.0f},
+    {NPE_JOINT_LEFT_HAND, 0.43292272f, 0.37860954f, 1.0f},
+    {NPE_JOINT_RIGHT_SHOULDER, 0.51725876f, 0.48693466f, 1.0f},
+    {NPE_JOINT_RIGHT_ELBOW, 0.3500515f, 0.41818976f, 1.0f},
+    {NPE_JOINT_RIGHT_WRIST, 0.20907341f, 0.3226182f, 1.0f},
+    {NPE_JOINT_RIGHT_HAND, 0.1777911f, 0.30141133f, 1.0f},
+};
+
+static const size_t DAB_R_COUNT = sizeof(DAB_R) / sizeof(DAB_R[0]);
+
+static void detect_default_poses(void) {
+    npe_detector *detector = npe_detector_new();
+    int32_t pose = NPE_NO_POSE;
+    float score = 0.0f;
+    CHECK(detector != NULL);
+    CHECK(npe_detector_detect(detector, DAB_R, DAB_R_COUNT, &pose, &score) == NPE_OK);
+    CHECK(pose == NPE_POSE_DAB_R);
+    CHECK(score > 0.99f);
+    CHECK(strcmp(npe_pose_name(pose), "DabR") == 0);
+
+    /* Without the right arm there's nothing to match */
+    CHECK(npe_detector_detect(detector, DAB_R, 4, &pose, NULL) == NPE_OK);
+    CHECK(pose == NPE_NO_POSE);
+    npe_detector_free(detector);
+}
+
+static void settings(void) {
+    npe_detector *detector = npe_detector_new();
+    npe_settings settings;
+    npe_settings invalid = {0.0f, 1.0f};
+    CHECK(npe_detector_get_settings(detector, &settings) == NPE_OK);
+    CHECK(settings.joint_cutoff > 0.0f);
+
+    settings.joint_cutoff = 0.5f;
+    CHECK(npe_detector_set_settings(detector, &settings) == NPE_OK);
+    settings.joint_cutoff = 0.0f;
+    CHECK(npe_detector_get_settings(detector, &settings) == NPE_OK);
+    CHECK(settings.joint_cutoff == 0.5f);
+
+    CHECK(npe_detector_set_settings(detector, &invalid) == NPE_INVALID_ARGUMENT);
+    CHECK(npe_last_error() != NULL);
+    npe_detector_free(detector);
+}
+
+static void load_library(void) {
+    npe_detector *detector = npe_detector_new();
+    int32_t pose = NPE_NO_POSE;
+    const char *roof_only =
+        "{\"Roof\": {"
+        "\"LeftShoulder\": [0.68835175, 0.49775392],"
+        "\"LeftElbow\": [0.5864927, 0.5303629],"
+        "\"LeftWrist\": [0.45776764, 0.40316057],"
+        "\"LeftHand\": [0.43292272, 0.37860954],"
+        "\"RightShoulder\": [0.51725876, 0.48693466],"
+        "\"RightElbow\": [0.3500515, 0.41818976],"
+        "\"RightWrist\": [0.20907341, 0.3226182],"
+        "\"RightHand\": [0.1777911, 0.30141133]}}";
+    CHECK(npe_detector_load_poses_str(detector, roof_only) == NPE_OK);
+    CHECK(npe_detector_detect(detector, DAB_R, DAB_R_COUNT, &pose, NULL) == NPE_OK);
+    CHECK(pose == NPE_POSE_ROOF);
+
+    CHECK(npe_detector_load_poses_str(detector, "{\"Nope\": {}}") == NPE_LIBRARY_ERROR);
+    CHECK(npe_last_error() != NULL);
+    CHECK(npe_detector_load_poses_file(detector, "does/not/exist.json") == NPE_LIBRARY_ERROR);
+
+    /* Failed loads keep the previous poses */
+    CHECK(npe_detector_detect(detector, DAB_R, DAB_R_COUNT, &pose, NULL) == NPE_OK);
+    CHECK(pose == NPE_POSE_ROOF);
+    npe_detector_free(detector);
+}
+
+static void null_pointers(void) {
+    int32_t pose;
+    CHECK(npe_detector_detect(NULL, DAB_R, DAB_R_COUNT, &pose, NULL) == NPE_NULL_POINTER);
+    CHECK(npe_detector_load_poses_str(NULL, "{}") == NPE_NULL_POINTER);
+    CHECK(npe_pose_name(-1) == NULL);
+    CHECK(npe_pose_name(6) == NULL);
+    npe_detector_free(NULL);
+}
+
+int main(void) {
+    detect_default_poses();
+    settings();
+    load_library();
+    null_pointers();
+    if (failures) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    printf("All checks passed\n");
+    return 0;
+}
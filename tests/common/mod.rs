@@ -0,0 +1,22 @@
+//! Fixtures shared between the integration tests
+
+#![allow(dead_code)]
+
+use nuitrack_pose_estimation as pe;
+
+use pe::{Detector, Joint, JointPos, Pose, Skeleton};
+
+/// One of the built in templates
+pub fn template(pose: Pose) -> JointPos {
+    Detector::default().poses()[&pose].clone()
+}
+
+/// A skeleton standing exactly in one of the built in poses
+pub fn skeleton(pose: Pose, id: i32) -> Skeleton {
+    let mut joints: Vec<Joint> = template(pose)
+        .into_iter()
+        .map(|(t, v)| Joint::new(t, v))
+        .collect();
+    joints.sort_by_key(|j| j.joint_type);
+    Skeleton { id, joints }
+}
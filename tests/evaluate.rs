@@ -0,0 +1,211 @@
+use nuitrack_pose_estimation as pe;
+
+mod common;
+
+use pe::evaluate::{evaluate, Frame};
+use pe::synth::{Distortion, Generator};
+use pe::tune::{steps, Objective, Tuner};
+use pe::{Detector, Pose, PoseData, Settings};
+
+fn frame(label: Option<Pose>, offset: f32) -> Frame {
+    let mut joints = common::skeleton(Pose::DabR, 1).joints;
+    for joint in &mut joints {
+        joint.proj.x += offset * (joint.joint_type as u32 % 3) as f32;
+    }
+    Frame { label, joints }
+}
+
+#[test]
+fn confusion_and_metrics() {
+    let frames = vec![
+        frame(Some(Pose::DabR), 0.0),
+        frame(Some(Pose::DabR), 0.1),
+        frame(None, 0.1),
+        frame(None, 0.0),
+    ];
+    let detector = Detector::new(Settings {
+        joint_cutoff: 0.01,
+        rotation_cutoff: 0.34,
+        ..Settings::default()
+    });
+    let evaluation = evaluate(&detector, &frames);
+
+    let dab_r = evaluation
+        .classes
+        .iter()
+        .position(|&c| c == Some(Pose::DabR))
+        .unwrap();
+    let none = evaluation.classes.iter().position(|c| c.is_none()).unwrap();
+    assert_eq!(evaluation.confusion[dab_r][dab_r], 1);
+    assert_eq!(evaluation.confusion[dab_r][none], 1);
+    assert_eq!(evaluation.confusion[none][dab_r], 1);
+    assert_eq!(evaluation.confusion[none][none], 1);
+
+    let metrics = evaluation
+        .metrics
+        .iter()
+        .find(|m| m.pose == Pose::DabR)
+        .unwrap();
+    assert_eq!(metrics.precision, 0.5);
+    assert_eq!(metrics.recall, 0.5);
+    assert_eq!(metrics.f1, 0.5);
+    assert_eq!(metrics.support, 2);
+    assert_eq!(evaluation.none_frames, 2);
+    assert_eq!(evaluation.false_positive_rate, 0.5);
+    assert_eq!(evaluation.macro_f1(), 0.5);
+    assert!(evaluation
+        .to_string()
+        .contains("False positive rate on none: 0.500"));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn read_labelled_frames() {
+    use pe::JointType;
+
+    let data = r#"{"label": "DabR", "joints": [{"type": "LeftShoulder", "proj": [0.5, 0.4, 0.0]}]}
+
+{"label": "none", "joints": [{"type": "RightHand", "proj": [0.1, 0.2, 0.0], "confidence": 0.5}]}
+"#;
+    let frames = pe::evaluate::read(data.as_bytes()).unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].label, Some(Pose::DabR));
+    assert_eq!(frames[0].joints[0].joint_type, JointType::LeftShoulder);
+    assert_eq!(frames[1].label, None);
+    assert_eq!(frames[1].joints[0].joint_type, JointType::RightHand);
+    assert_eq!(frames[1].joints[0].confidence, 0.5);
+
+    let bad = r#"{"label": "Dab", "joints": []}"#;
+    assert!(pe::evaluate::read(bad.as_bytes()).is_err());
+}
+
+#[test]
+fn tune_cutoffs() {
+    let frames = vec![
+        frame(Some(Pose::DabR), 0.0),
+        frame(Some(Pose::DabR), 0.002),
+        frame(None, 0.05),
+        frame(None, 0.1),
+    ];
+    let tuner = Tuner {
+        joint_cutoffs: steps(0.01, 0.3, 30),
+        rotation_cutoffs: vec![0.34],
+        objective: Objective::MacroF1WithFalsePositiveCap(0.0),
+        per_pose: true,
+    };
+    let tuned = tuner.tune(&Detector::default(), &frames).unwrap();
+    assert_eq!(tuned.score, 1.0);
+    assert_eq!(tuned.evaluation.false_positive_rate, 0.0);
+
+    let detector = Detector::new(tuned.settings.clone());
+    assert_eq!(evaluate(&detector, &frames), tuned.evaluation);
+}
+
+#[test]
+fn tune_agrees_with_detector() {
+    // Tilted and mirrored skeletons, which the detector
+    // rejects or matches after aligning each side
+    let mut settings = Settings {
+        max_tilt: Some(0.15),
+        ..Settings::default()
+    };
+    settings.either_hand.extend(Pose::ALL.iter().cloned());
+    let detector = Detector::new(settings);
+    let limits = Distortion {
+        rotation: 0.3,
+        noise: 0.01,
+        ..Distortion::default()
+    };
+    let mut generator = Generator::new(7);
+    let poses = detector.poses();
+    let mut frames = Vec::new();
+    for (i, &pose) in Pose::ALL.iter().cycle().take(60).enumerate() {
+        let distortion = generator.random_distortion(&limits);
+        let mut joints = generator.skeleton(&poses[&pose], &distortion).joints;
+        if i % 2 == 1 {
+            for joint in &mut joints {
+                joint.joint_type = joint.joint_type.mirrored();
+                joint.proj.x = 1.0 - joint.proj.x;
+            }
+        }
+        frames.push(Frame {
+            label: Some(pose),
+            joints,
+        });
+    }
+    let tuner = Tuner {
+        joint_cutoffs: steps(0.02, 0.3, 8),
+        rotation_cutoffs: steps(0.05, 0.6, 8),
+        objective: Objective::MacroF1,
+        per_pose: true,
+    };
+    let tuned = tuner.tune(&detector, &frames).unwrap();
+    let detector = Detector::new(tuned.settings.clone());
+    assert_eq!(evaluate(&detector, &frames), tuned.evaluation);
+}
+
+#[test]
+fn tune_either_hand() {
+    // Turned so the stored Roof lines up exactly but is turned too far,
+    // while its mirror image is a little off but turned less.
+    // The detector's own cutoffs match neither and keep the stored side
+    let mut poses = PoseData::new();
+    poses.insert(Pose::Roof, common::template(Pose::Roof));
+    let mut settings = Settings {
+        joint_cutoff: 0.001,
+        rotation_cutoff: 0.001,
+        ..Settings::default()
+    };
+    settings.either_hand.insert(Pose::Roof);
+    let detector = Detector::with_poses(settings, poses);
+    let mut joints = common::skeleton(Pose::Roof, 1).joints;
+    let (sin, cos) = 0.26f32.sin_cos();
+    for joint in &mut joints {
+        let (x, y) = (joint.proj.x, joint.proj.y);
+        joint.proj.x = x * cos - y * sin;
+        joint.proj.y = x * sin + y * cos;
+    }
+    let frames = vec![Frame {
+        label: Some(Pose::Roof),
+        joints,
+    }];
+    let tuner = Tuner {
+        joint_cutoffs: steps(0.05, 0.1, 2),
+        rotation_cutoffs: steps(0.2, 0.22, 2),
+        objective: Objective::MacroF1,
+        per_pose: false,
+    };
+    let tuned = tuner.tune(&detector, &frames).unwrap();
+    detector.set_settings(tuned.settings.clone());
+    assert_eq!(detector.detect(&frames[0].joints), Some(Pose::Roof));
+    assert_eq!(evaluate(&detector, &frames), tuned.evaluation);
+}
+
+#[test]
+fn tune_nothing_meets_objective() {
+    let frames = vec![frame(None, 0.0)];
+    let tuner = Tuner {
+        joint_cutoffs: vec![0.3],
+        rotation_cutoffs: vec![0.34],
+        objective: Objective::MacroF1WithFalsePositiveCap(0.0),
+        per_pose: false,
+    };
+    assert!(tuner.tune(&Detector::default(), &frames).is_none());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn save_and_load_settings() {
+    let mut settings = Settings::default();
+    settings.overrides.insert(
+        Pose::Roof,
+        pe::Cutoffs {
+            joint_cutoff: 0.2,
+            rotation_cutoff: 0.5,
+        },
+    );
+    let path = std::env::temp_dir().join("npe_save_and_load_settings.json");
+    settings.save(&path).unwrap();
+    assert_eq!(Settings::load(&path).unwrap(), settings);
+    std::fs::remove_file(path).ok();
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "ffi")]
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const HEADER: &str = "include/nuitrack_pose_estimation.h";
+
+/// The checked in header has to match what cbindgen makes from `src/ffi.rs`.
+/// Run with `NPE_UPDATE_HEADER=1` to write it instead
+#[test]
+fn header_up_to_date() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = cbindgen::Config::from_file(root.join("cbindgen.toml")).unwrap();
+    let bindings = cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(root.join("src/ffi.rs"))
+        .generate()
+        .unwrap();
+    let mut generated = Vec::new();
+    bindings.write(&mut generated);
+    let generated = String::from_utf8(generated).unwrap();
+
+    let path = root.join(HEADER);
+    if env::var_os("NPE_UPDATE_HEADER").is_some() {
+        fs::write(&path, &generated).unwrap();
+    }
+    let header = fs::read_to_string(&path).unwrap();
+    assert!(
+        header == generated,
+        "{} is out of date, run `NPE_UPDATE_HEADER=1 cargo test --features ffi --test header`",
+        HEADER
+    );
+}
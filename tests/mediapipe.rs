@@ -0,0 +1,78 @@
+use nuitrack_pose_estimation as pe;
+
+use pe::mediapipe::{self, Landmark};
+use pe::{Detector, JointType, Pose};
+
+/// DabR arms as (landmark, x, y)
+const DAB_R: [(usize, f32, f32); 6] = [
+    (11, 0.68835175, 0.49775392),
+    (13, 0.5864927, 0.5303629),
+    (15, 0.45776764, 0.40316057),
+    (12, 0.51725876, 0.48693466),
+    (14, 0.3500515, 0.41818976),
+    (16, 0.20907341, 0.3226182),
+];
+
+/// Template hands as (wrist, pinky, index, x, y)
+const HANDS: [(usize, usize, usize, f32, f32); 2] = [
+    (15, 17, 19, 0.43292272, 0.37860954),
+    (16, 18, 20, 0.1777911, 0.30141133),
+];
+
+fn landmarks() -> Vec<Landmark> {
+    let mut landmarks = vec![
+        Landmark {
+            x: 0.5,
+            y: 0.5,
+            z: 0.0,
+            visibility: 0.9,
+        };
+        33
+    ];
+    for &(i, x, y) in DAB_R.iter() {
+        landmarks[i].x = x;
+        landmarks[i].y = y;
+    }
+    // Put the knuckles past the hand so the middle of the
+    // wrist and knuckles is where the template wants the hand
+    for &(wrist, pinky, index, x, y) in HANDS.iter() {
+        let w = landmarks[wrist];
+        for &i in [pinky, index].iter() {
+            landmarks[i].x = w.x + (x - w.x) * 1.5;
+            landmarks[i].y = w.y + (y - w.y) * 1.5;
+        }
+    }
+    landmarks[17].visibility = 0.4;
+    landmarks
+}
+
+#[test]
+fn landmarks_to_joints() {
+    let joints = mediapipe::joints(&landmarks()).unwrap();
+    let hand = joints
+        .iter()
+        .find(|j| j.joint_type == JointType::LeftHand)
+        .unwrap();
+    assert!((hand.proj.x - 0.43292272).abs() < 0.0001);
+    assert!((hand.proj.y - 0.37860954).abs() < 0.0001);
+    assert_eq!(hand.confidence, 0.4);
+    assert_eq!(Detector::default().detect(&joints), Some(Pose::DabR));
+
+    assert!(mediapipe::joints(&landmarks()[..20]).is_none());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn read_landmark_dumps() {
+    let frame = serde_json::to_string(&landmarks()).unwrap();
+    let frames = mediapipe::read(frame.as_bytes()).unwrap();
+    assert_eq!(frames.len(), 1);
+
+    let dump = format!(r#"[{{"landmarks": {0}}}, {{"landmarks": {0}}}]"#, frame);
+    let frames = mediapipe::read(dump.as_bytes()).unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(Detector::default().detect(&frames[1]), Some(Pose::DabR));
+
+    let short = r#"[{"x": 0.5, "y": 0.5, "z": 0.0, "visibility": 1.0}]"#;
+    assert!(mediapipe::read(short.as_bytes()).is_err());
+}
@@ -0,0 +1,87 @@
+use nuitrack_pose_estimation as pe;
+
+use pe::{openpose, Detector, JointType, Pose};
+
+const WIDTH: f32 = 640.0;
+const HEIGHT: f32 = 480.0;
+
+/// DabR arms as (BODY_25, COCO) keypoint indices
+const DAB_R: [(usize, usize, f32, f32); 6] = [
+    (5, 5, 0.68835175, 0.49775392),
+    (6, 7, 0.5864927, 0.5303629),
+    (7, 9, 0.45776764, 0.40316057),
+    (2, 6, 0.51725876, 0.48693466),
+    (3, 8, 0.3500515, 0.41818976),
+    (4, 10, 0.20907341, 0.3226182),
+];
+
+fn keypoints(count: usize, coco: bool) -> Vec<f32> {
+    let mut keypoints = vec![0.0; count * 3];
+    for &(body_25, coco_index, x, y) in DAB_R.iter() {
+        let i = if coco { coco_index } else { body_25 } * 3;
+        keypoints[i] = x * WIDTH;
+        keypoints[i + 1] = y * HEIGHT;
+        keypoints[i + 2] = if coco { 2.0 } else { 0.8 };
+    }
+    keypoints
+}
+
+#[test]
+fn body_25_arms() {
+    let joints = openpose::body_25(&keypoints(25, false), WIDTH, HEIGHT);
+    // Six arm joints found plus two hands
+    assert_eq!(joints.len(), 8);
+    let hand = joints
+        .iter()
+        .find(|j| j.joint_type == JointType::RightHand)
+        .unwrap();
+    // Template has the hand at (0.178, 0.301)
+    assert!((hand.proj.x - 0.1777911).abs() < 0.01);
+    assert!((hand.proj.y - 0.30141133).abs() < 0.01);
+    assert_eq!(hand.confidence, 0.8);
+
+    assert_eq!(Detector::default().detect(&joints), Some(Pose::DabR));
+}
+
+#[test]
+fn coco_arms() {
+    let mut keypoints = keypoints(17, true);
+    // Occluded left wrist
+    keypoints[9 * 3 + 2] = 1.0;
+    let joints = openpose::coco(&keypoints, WIDTH, HEIGHT);
+    assert_eq!(joints.len(), 8);
+    let wrist = joints
+        .iter()
+        .find(|j| j.joint_type == JointType::LeftWrist)
+        .unwrap();
+    assert_eq!(wrist.confidence, 0.5);
+    assert_eq!(Detector::default().detect(&joints), Some(Pose::DabR));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn read_openpose_and_coco() {
+    let frame = format!(
+        r#"{{"version": 1.3, "people": [{{"person_id": [-1], "pose_keypoints_2d": {:?}}}]}}"#,
+        keypoints(25, false)
+    );
+    let skeletons = openpose::read(frame.as_bytes(), WIDTH, HEIGHT).unwrap();
+    assert_eq!(skeletons.len(), 1);
+    assert_eq!(skeletons[0].joints.len(), 8);
+
+    let dataset = format!(
+        r#"{{"images": [{{"id": 4, "width": 640, "height": 480, "file_name": "a.jpg"}}],
+            "annotations": [{{"id": 9, "image_id": 4, "num_keypoints": 6, "keypoints": {:?}}}]}}"#,
+        keypoints(17, true)
+    );
+    let annotations = openpose::read_coco(dataset.as_bytes()).unwrap();
+    assert_eq!(annotations[0].image_id, 4);
+    assert_eq!(annotations[0].skeleton.id, 9);
+    assert_eq!(
+        Detector::default().detect(&annotations[0].skeleton.joints),
+        Some(Pose::DabR)
+    );
+
+    let short = r#"{"people": [{"pose_keypoints_2d": [1.0, 2.0, 0.5]}]}"#;
+    assert!(openpose::read(short.as_bytes(), WIDTH, HEIGHT).is_err());
+}
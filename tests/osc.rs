@@ -0,0 +1,105 @@
+use nuitrack_pose_estimation as pe;
+
+mod common;
+
+use pe::osc::{Addresses, Arg, Message, Sender};
+use pe::tracker::{Event, Tracker};
+use pe::{Detector, Pose};
+use std::net::UdpSocket;
+use std::time::Duration;
+
+#[test]
+fn encode_and_decode() {
+    let message = Message {
+        address: "/pose/1".to_string(),
+        args: vec![Arg::Str("DabR".to_string()), Arg::Float(0.5), Arg::Int(-3)],
+    };
+    let bytes = message.encode();
+    assert_eq!(bytes.len() % 4, 0);
+    assert_eq!(&bytes[..8], b"/pose/1\0");
+    assert_eq!(&bytes[8..16], b",sfi\0\0\0\0");
+    assert_eq!(&bytes[16..24], b"DabR\0\0\0\0");
+    assert_eq!(&bytes[24..28], &[0x3f, 0x00, 0x00, 0x00]);
+    assert_eq!(Message::decode(&bytes).unwrap(), message);
+    assert!(Message::decode(&bytes[..22]).is_err());
+}
+
+#[test]
+fn track_lifecycle() {
+    let mut tracker = Tracker::new();
+    assert_eq!(
+        tracker.update(vec![(1, None), (2, Some(Pose::Roof))]),
+        vec![
+            Event::UserFound(1),
+            Event::UserFound(2),
+            Event::PoseStarted(2, Pose::Roof),
+        ]
+    );
+    assert_eq!(
+        tracker.update(vec![(1, None), (2, Some(Pose::Roof))]),
+        vec![]
+    );
+    assert_eq!(
+        tracker.update(vec![(1, Some(Pose::DabR)), (2, Some(Pose::DabL))]),
+        vec![
+            Event::PoseStarted(1, Pose::DabR),
+            Event::PoseEnded(2, Pose::Roof),
+            Event::PoseStarted(2, Pose::DabL),
+        ]
+    );
+    assert_eq!(
+        tracker.update(vec![(2, None)]),
+        vec![
+            Event::PoseEnded(1, Pose::DabR),
+            Event::UserLost(1),
+            Event::PoseEnded(2, Pose::DabL),
+        ]
+    );
+    assert_eq!(tracker.poses().collect::<Vec<_>>(), vec![(2, None)]);
+}
+
+#[test]
+fn send_to_local_socket() {
+    let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+    receiver
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let mut sender = Sender::new(receiver.local_addr().unwrap()).unwrap();
+    sender.addresses = Addresses {
+        score: "/score/{pose}/{user}".to_string(),
+        ..Addresses::default()
+    };
+
+    let detector = Detector::default();
+    let events = sender
+        .send_frame(&detector, &[common::skeleton(Pose::DabR, 4)])
+        .unwrap();
+    assert_eq!(
+        events,
+        vec![Event::UserFound(4), Event::PoseStarted(4, Pose::DabR)]
+    );
+
+    let mut buf = [0; 1024];
+    let mut messages = Vec::new();
+    // Detection, six scores and two events
+    for _ in 0..9 {
+        let n = receiver.recv(&mut buf).unwrap();
+        messages.push(Message::decode(&buf[..n]).unwrap());
+    }
+    assert_eq!(messages[0].address, "/pose/4");
+    assert_eq!(messages[0].args, vec![Arg::Str("DabR".to_string())]);
+    assert_eq!(messages[1].address, "/score/DabR/4");
+    match messages[1].args[0] {
+        Arg::Float(score) => assert!(score > 0.9),
+        ref a => panic!("Unexpected argument {:?}", a),
+    }
+    assert_eq!(messages[7].address, "/event/4/found");
+    assert_eq!(messages[8].address, "/event/4/started");
+    assert_eq!(
+        messages[8].args,
+        vec![
+            Arg::Str("started".to_string()),
+            Arg::Str("DabR".to_string())
+        ]
+    );
+}
@@ -1,12 +1,15 @@
 use nalgebra_glm as glm;
 use nuitrack_pose_estimation as pe;
-use nuitrack_rs;
 
 use glm::Vec2;
-use nuitrack_rs::{Joint, JointType, Orientation, SkeletonFeed, Vector3};
-use pe::{Detector, Pose, PoseData, Settings};
+use pe::{
+    Bounds, Chirality, Cutoffs, Detector, Error, Failure, Implausible, Joint, JointType, Library,
+    Normalisation, Plausibility, Pose, PoseData, Resolution, Rotation, Settings, Skeleton, Tester,
+};
 use std::collections::HashMap;
 use std::iter::FromIterator;
+use std::sync::Arc;
+use std::thread;
 
 const DAB_R: (&'static str, [(JointType, (f32, f32)); 8]) = (
     "DabR",
@@ -33,27 +36,12 @@ fn dab_r() -> PoseData {
     poses
 }
 
-fn skeleton(joints: &[(u32, Vec2)]) -> SkeletonFeed {
-    let orient = Orientation { matrix: [1.0; 9] };
+fn skeleton(joints: &[(u32, Vec2)]) -> Skeleton {
     let joints = joints
         .iter()
-        .map(|&(type_, v)| Joint {
-            type_,
-            confidence: 1.0,
-            orient,
-            proj: Vector3 {
-                x: v.x,
-                y: v.y,
-                z: 0.0,
-            },
-            real: Vector3 {
-                x: 0.0,
-                y: 0.0,
-                z: 0.0,
-            },
-        })
+        .map(|&(id, v)| Joint::new(JointType::from_u32(id).unwrap(), v))
         .collect();
-    SkeletonFeed { id: 1, joints }
+    Skeleton { id: 1, joints }
 }
 
 fn identity_mock() -> Vec<(u32, Vec2)> {
@@ -69,12 +57,27 @@ fn identity_mock() -> Vec<(u32, Vec2)> {
     ]
 }
 
+/// Rotate, scale and move a skeleton about its right shoulder
+fn transform(joints: &[(u32, Vec2)], angle: f32, scale: f32, offset: Vec2) -> Vec<(u32, Vec2)> {
+    let origin = joints[4].1;
+    let (sin, cos) = angle.sin_cos();
+    joints
+        .iter()
+        .map(|&(t, v)| {
+            let d = (v - origin) * scale;
+            let rotated = glm::vec2(d.x * cos - d.y * sin, d.x * sin + d.y * cos);
+            (t, origin + rotated + offset)
+        })
+        .collect()
+}
+
 #[test]
 fn match_identity() {
     let mock_skeleton = identity_mock();
     let settings = Settings {
         rotation_cutoff: 0.34,
         joint_cutoff: 0.01,
+        ..Settings::default()
     };
     let skeleton = skeleton(&mock_skeleton);
     let tester = Detector::with_poses(settings, dab_r());
@@ -91,6 +94,7 @@ fn match_close() {
     let settings = Settings {
         rotation_cutoff: 0.34,
         joint_cutoff: 0.01,
+        ..Settings::default()
     };
     let skeleton = skeleton(&mock_skeleton);
     let tester = Detector::with_poses(settings, dab_r());
@@ -107,6 +111,7 @@ fn nomatch_joint_cutoff() {
     let settings = Settings {
         rotation_cutoff: 0.34,
         joint_cutoff: 0.01,
+        ..Settings::default()
     };
     let skeleton = skeleton(&mock_skeleton);
     let tester = Detector::with_poses(settings, dab_r());
@@ -124,6 +129,7 @@ fn nomatch_rotation_cutoff() {
     let settings = Settings {
         rotation_cutoff: 0.34,
         joint_cutoff: 0.01,
+        ..Settings::default()
     };
     let skeleton = skeleton(&mock_skeleton);
     let tester = Detector::with_poses(settings, dab_r());
@@ -134,14 +140,16 @@ fn nomatch_rotation_cutoff() {
 #[test]
 fn match_rotation_close() {
     // 15deg rotation
-    let mut mock_skeleton = identity_mock();
-    mock_skeleton[0].1 = glm::vec2(0.536068538626, 0.658951909094);
-    mock_skeleton[1].1 = glm::vec2(0.429240426524, 0.664086702989);
-    mock_skeleton[2].1 = glm::vec2(0.337823952165, 0.507902190168);
-    mock_skeleton[3].1 = glm::vec2(0.320179876426, 0.477757377759);
+    let mock_skeleton = transform(
+        &identity_mock(),
+        15f32.to_radians(),
+        1.0,
+        glm::vec2(0.0, 0.0),
+    );
     let settings = Settings {
         rotation_cutoff: 0.34,
         joint_cutoff: 0.08,
+        ..Settings::default()
     };
     let skeleton = skeleton(&mock_skeleton);
     let tester = Detector::with_poses(settings, dab_r());
@@ -159,6 +167,7 @@ fn match_allign() {
     let settings = Settings {
         rotation_cutoff: 0.34,
         joint_cutoff: 0.01,
+        ..Settings::default()
     };
     let skeleton = skeleton(&mock_skeleton);
     let tester = Detector::with_poses(settings, dab_r());
@@ -175,9 +184,647 @@ fn match_scale() {
     let settings = Settings {
         rotation_cutoff: 0.34,
         joint_cutoff: 0.01,
+        ..Settings::default()
     };
     let skeleton = skeleton(&mock_skeleton);
     let tester = Detector::with_poses(settings, dab_r());
     let result = tester.detect(&skeleton.joints);
     assert_eq!(result, Some(Pose::DabR));
 }
+
+#[test]
+fn match_other_resolution() {
+    // The template pose captured again at 1280x720,
+    // a bit closer to the camera
+    let mock_skeleton: Vec<_> = identity_mock()
+        .into_iter()
+        .map(|(t, v)| {
+            let pixels = glm::vec2(v.x * 640.0, v.y * 480.0) * 1.2 + glm::vec2(200.0, 20.0);
+            (t, glm::vec2(pixels.x / 1280.0, pixels.y / 720.0))
+        })
+        .collect();
+    let skeleton = skeleton(&mock_skeleton);
+    let settings = Settings {
+        rotation_cutoff: 0.05,
+        joint_cutoff: 0.005,
+        ..Settings::default()
+    };
+    let detector = Detector::with_poses(settings.clone(), dab_r());
+    assert_eq!(detector.detect(&skeleton.joints), None);
+
+    let settings = Settings {
+        resolution: Some(Resolution::new(1280, 720)),
+        ..settings
+    };
+    let library = Library {
+        poses: dab_r(),
+        resolution: Some(Resolution::NUITRACK),
+    };
+    let detector = Detector::with_library(settings.clone(), library);
+    let report = detector.detect_explain(&skeleton.joints);
+    assert_eq!(report.pose, Some(Pose::DabR));
+    assert!(report.poses[0].furthest().unwrap() < 0.0001);
+
+    // Corrections are back in image coordinates
+    let corrections = report.poses[0].corrections().unwrap();
+    let hand = corrections
+        .iter()
+        .find(|c| c.joint == JointType::RightHand)
+        .unwrap();
+    assert!(glm::distance(&hand.position, &mock_skeleton[7].1) < 0.0001);
+
+    // The built in templates know they're 640x480
+    let detector = Detector::new(settings);
+    assert_eq!(detector.template_resolution(), Some(Resolution::NUITRACK));
+    let report = detector.detect_explain(&skeleton.joints);
+    assert_eq!(report.pose, Some(Pose::DabR));
+    assert!(report.poses[0].furthest().unwrap() < 0.0001);
+}
+
+#[test]
+fn match_torso_frame() {
+    let mut mock_skeleton = transform(&identity_mock(), 0.1, 0.8, glm::vec2(0.05, 0.02));
+    let shoulder_width = glm::distance(&mock_skeleton[0].1, &mock_skeleton[4].1);
+    // Right hand a tenth of a shoulder width out
+    mock_skeleton[7].1.y += shoulder_width * 0.1;
+    let settings = Settings {
+        normalisation: Normalisation::Torso,
+        joint_cutoff: 0.15,
+        ..Settings::default()
+    };
+    let skeleton = skeleton(&mock_skeleton);
+    let detector = Detector::with_poses(settings, dab_r());
+    let report = detector.detect_explain(&skeleton.joints);
+    assert_eq!(report.pose, Some(Pose::DabR));
+    let pose = &report.poses[0];
+    assert!((pose.rotation.unwrap() - 0.1).abs() < 0.0001);
+    assert!((pose.scale.unwrap() - 1.25).abs() < 0.0001);
+    let furthest = pose.furthest_joint().unwrap();
+    assert_eq!(furthest.joint, JointType::RightHand);
+    assert!((furthest.distance - 0.1).abs() < 0.0001);
+    for jd in pose
+        .joints
+        .iter()
+        .filter(|jd| jd.joint != JointType::RightHand)
+    {
+        assert!(jd.distance < 0.0001);
+    }
+    let corrections = pose.corrections().unwrap();
+    assert!(glm::distance(&corrections[0].position, &mock_skeleton[7].1) < 0.0001);
+}
+
+#[test]
+fn normalisations_agree_on_rotation() {
+    let rotated = skeleton(&transform(&identity_mock(), 0.3, 0.9, glm::vec2(0.02, 0.0)));
+    for &normalisation in [Normalisation::Arms, Normalisation::Torso].iter() {
+        let settings = Settings {
+            normalisation,
+            rotation_cutoff: 0.5,
+            ..Settings::default()
+        };
+        let detector = Detector::with_poses(settings, dab_r());
+        let report = detector.detect_explain(&rotated.joints);
+        assert_eq!(report.pose, Some(Pose::DabR), "{:?}", normalisation);
+        let rotation = report.poses[0].rotation.unwrap();
+        assert!(
+            (rotation - 0.3).abs() < 0.0001,
+            "{:?} {}",
+            normalisation,
+            rotation
+        );
+    }
+}
+
+#[test]
+fn reject_tilted_torso() {
+    let settings = Settings {
+        max_tilt: Some(0.2),
+        ..Settings::default()
+    };
+    let detector = Detector::with_poses(settings, dab_r());
+    let level = skeleton(&identity_mock());
+    assert_eq!(detector.detect(&level.joints), Some(Pose::DabR));
+
+    let tilted = transform(&identity_mock(), 0.3, 1.0, glm::vec2(0.0, 0.0));
+    let report = detector.detect_explain(&skeleton(&tilted).joints);
+    assert_eq!(report.pose, None);
+    match report.poses[0].failure {
+        Some(Failure::TorsoTilted(tilt)) => assert!(tilt > 0.3),
+        f => panic!("Unexpected failure {:?}", f),
+    }
+    assert!(report.evaluated());
+}
+
+#[test]
+fn rotation_modes() {
+    let upright = Detector::with_poses(Settings::default(), dab_r());
+    let lying = skeleton(&transform(&identity_mock(), 1.6, 1.0, glm::vec2(0.0, 0.0)));
+    assert_eq!(upright.detect(&lying.joints), None);
+    match upright.detect_explain(&lying.joints).poses[0].failure {
+        Some(Failure::RotationExceeded(r)) => assert!((r - 1.6).abs() < 0.0001),
+        f => panic!("Unexpected failure {:?}", f),
+    }
+
+    upright.update_settings(|s| s.rotation = Rotation::Free);
+    assert_eq!(upright.detect(&lying.joints), Some(Pose::DabR));
+    let upside_down = skeleton(&transform(&identity_mock(), -3.0, 1.0, glm::vec2(0.0, 0.0)));
+    assert_eq!(upright.detect(&upside_down.joints), Some(Pose::DabR));
+
+    // Leaning clockwise but not anticlockwise
+    let leaning = Rotation::Range {
+        clockwise: 0.6,
+        anticlockwise: 0.1,
+    };
+    let settings = Settings {
+        rotation_overrides: HashMap::from_iter(vec![(Pose::DabR, leaning)]),
+        ..Settings::default()
+    };
+    let detector = Detector::with_poses(settings, dab_r());
+    let clockwise = skeleton(&transform(&identity_mock(), 0.5, 1.0, glm::vec2(0.0, 0.0)));
+    let anticlockwise = skeleton(&transform(&identity_mock(), -0.5, 1.0, glm::vec2(0.0, 0.0)));
+    assert_eq!(detector.detect(&clockwise.joints), Some(Pose::DabR));
+    assert_eq!(detector.detect(&anticlockwise.joints), None);
+    assert_eq!(detector.settings().rotation(Pose::Roof), Rotation::Upright);
+
+    let backwards = Settings {
+        rotation: Rotation::Range {
+            clockwise: -0.1,
+            anticlockwise: 0.1,
+        },
+        ..Settings::default()
+    };
+    match backwards.validate() {
+        Err(Error::InvalidSettings(reason)) => assert!(reason.contains("clockwise"), "{}", reason),
+        r => panic!("Unexpected result {:?}", r),
+    }
+}
+
+/// Joints at these image positions, with 3D positions
+/// in millimetres of arms held out level
+fn with_real(joints: &[(u32, Vec2)], upper_arm: f32, forearm: f32) -> Vec<Joint> {
+    let elbow = 180.0 + upper_arm;
+    joints
+        .iter()
+        .map(|&(id, v)| {
+            let t = JointType::from_u32(id).unwrap();
+            let reach = match t {
+                JointType::RightShoulder | JointType::LeftShoulder => 180.0,
+                JointType::RightElbow | JointType::LeftElbow => elbow,
+                JointType::RightWrist | JointType::LeftWrist => elbow + forearm,
+                _ => elbow + forearm + 80.0,
+            };
+            let side = if t.name().starts_with("Left") {
+                1.0
+            } else {
+                -1.0
+            };
+            let mut joint = Joint::new(t, v);
+            joint.real = glm::vec3(side * reach, 300.0, 2000.0);
+            joint
+        })
+        .collect()
+}
+
+#[test]
+fn reject_implausible() {
+    let settings = Settings {
+        plausibility: Some(Plausibility::default()),
+        ..Settings::default()
+    };
+    let detector = Detector::new(settings.clone());
+    for (&pose, template) in detector.poses().iter() {
+        let joints: Vec<_> = template.iter().map(|(&t, &v)| Joint::new(t, v)).collect();
+        assert_eq!(detector.detect(&joints), Some(pose));
+    }
+
+    let detector = Detector::with_poses(settings, dab_r());
+    let mut swapped_hands = identity_mock();
+    let right_hand = swapped_hands[7].1;
+    swapped_hands[7].1 = swapped_hands[3].1;
+    swapped_hands[3].1 = right_hand;
+    let report = detector.detect_explain(&skeleton(&swapped_hands).joints);
+    assert_eq!(report.pose, None);
+    assert_eq!(report.rejected, Some(Implausible::HandsSwapped));
+    assert!(report.poses.is_empty());
+    assert!(!report.evaluated());
+    assert_eq!(
+        detector
+            .diagnose(Pose::DabR, &skeleton(&swapped_hands).joints)
+            .unwrap_err(),
+        Error::Implausible(Implausible::HandsSwapped)
+    );
+
+    // Clapping with the hands a little past each other
+    let mut clap = identity_mock();
+    clap[2].1 = glm::vec2(0.62, 0.62);
+    clap[3].1 = glm::vec2(0.595, 0.56);
+    clap[6].1 = glm::vec2(0.58, 0.62);
+    clap[7].1 = glm::vec2(0.605, 0.56);
+    let report = detector.detect_explain(&skeleton(&clap).joints);
+    assert_eq!(report.rejected, None);
+
+    let swapped_sides: Vec<_> = identity_mock()
+        .into_iter()
+        .map(|(t, v)| (JointType::from_u32(t).unwrap().mirrored().id(), v))
+        .collect();
+    let report = detector.detect_explain(&skeleton(&swapped_sides).joints);
+    assert_eq!(report.rejected, Some(Implausible::SidesSwapped));
+
+    // Lengths are checked in 3D, whatever the arms look like in the image
+    let report = detector.detect_explain(&with_real(&identity_mock(), 300.0, 260.0));
+    assert_eq!(report.rejected, None);
+    assert_eq!(report.pose, Some(Pose::DabR));
+    // A forearm too long for anyone
+    match detector
+        .detect_explain(&with_real(&identity_mock(), 300.0, 700.0))
+        .rejected
+    {
+        Some(Implausible::BoneLength(JointType::RightWrist, l)) => {
+            assert!((l - 700.0).abs() < 0.01)
+        }
+        r => panic!("Unexpected rejection {:?}", r),
+    }
+    // Forearms twice the upper arms
+    match detector
+        .detect_explain(&with_real(&identity_mock(), 200.0, 400.0))
+        .rejected
+    {
+        Some(Implausible::BoneRatio(JointType::RightWrist, r)) => assert!((r - 2.0).abs() < 0.01),
+        r => panic!("Unexpected rejection {:?}", r),
+    }
+
+    let backwards = Settings {
+        plausibility: Some(Plausibility {
+            hand: Bounds::new(50.0, 10.0),
+            ..Plausibility::default()
+        }),
+        ..Settings::default()
+    };
+    match backwards.validate() {
+        Err(Error::InvalidSettings(reason)) => assert!(reason.contains("hand"), "{}", reason),
+        r => panic!("Unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn mirrored_camera() {
+    // DabR as it arrives when the camera is mirrored
+    let mock_skeleton: Vec<_> = identity_mock()
+        .into_iter()
+        .map(|(t, v)| {
+            let t = JointType::from_u32(t).unwrap().mirrored().id();
+            (t, glm::vec2(1.0 - v.x, v.y))
+        })
+        .collect();
+    let skeleton = skeleton(&mock_skeleton);
+    let detector = Detector::default();
+    assert_eq!(detector.detect(&skeleton.joints), Some(Pose::DabL));
+
+    detector.update_settings(|s| s.mirror = true);
+    assert_eq!(detector.detect(&skeleton.joints), Some(Pose::DabR));
+    let report = detector.diagnose(Pose::DabR, &skeleton.joints).unwrap();
+    assert!(report.furthest().unwrap() < 0.0001);
+    // Guidance is on the mirrored display
+    let corrections = detector
+        .guidance(Pose::DabR, &skeleton.joints)
+        .unwrap()
+        .unwrap();
+    let hand = corrections
+        .iter()
+        .find(|c| c.joint == JointType::RightHand)
+        .unwrap();
+    assert!(glm::distance(&hand.position, &identity_mock()[7].1) < 0.0001);
+}
+
+#[test]
+fn match_either_hand() {
+    let dab_l: Vec<_> = identity_mock()
+        .into_iter()
+        .map(|(t, v)| {
+            let t = JointType::from_u32(t).unwrap().mirrored().id();
+            (t, glm::vec2(1.0 - v.x, v.y))
+        })
+        .collect();
+    let skeleton_l = skeleton(&dab_l);
+    let skeleton_r = skeleton(&identity_mock());
+    let detector = Detector::with_poses(Settings::default(), dab_r());
+    assert_eq!(detector.detect(&skeleton_l.joints), None);
+
+    detector.update_settings(|s| {
+        s.either_hand.insert(Pose::DabR);
+    });
+    let report = detector.detect_explain(&skeleton_l.joints);
+    assert_eq!(report.pose, Some(Pose::DabR));
+    assert_eq!(report.chirality(), Some(Chirality::Mirrored));
+    assert!(report.poses[0].furthest().unwrap() < 0.0001);
+
+    let report = detector.detect_explain(&skeleton_r.joints);
+    assert_eq!(report.pose, Some(Pose::DabR));
+    assert_eq!(report.chirality(), Some(Chirality::Same));
+    let diagnosed = detector.diagnose(Pose::DabR, &skeleton_l.joints).unwrap();
+    assert_eq!(diagnosed.chirality, Chirality::Mirrored);
+}
+
+#[test]
+fn explain_every_pose() {
+    let mut mock_skeleton = identity_mock();
+    mock_skeleton[5].1 = glm::vec2(0.4500515, 0.41818976);
+    let settings = Settings {
+        rotation_cutoff: 0.34,
+        joint_cutoff: 0.01,
+        ..Settings::default()
+    };
+    let skeleton = skeleton(&mock_skeleton);
+    let tester = Detector::new(settings);
+    let report = tester.detect_explain(&skeleton.joints);
+    assert_eq!(report.pose, None);
+    assert_eq!(report.poses.len(), 6);
+    for pose in &report.poses {
+        assert_eq!(pose.skeleton.len(), 8);
+        assert_eq!(pose.template.len(), 8);
+        assert_eq!(pose.joints.len(), 8);
+        assert!(pose.scale.is_some());
+        assert!(pose.failure.is_some());
+    }
+}
+
+#[test]
+fn explain_missing_joint() {
+    let mut mock_skeleton = identity_mock();
+    mock_skeleton.pop();
+    let skeleton = skeleton(&mock_skeleton);
+    let tester = Detector::with_poses(Settings::default(), dab_r());
+    let report = tester.detect_explain(&skeleton.joints);
+    assert_eq!(report.pose, None);
+    assert_eq!(
+        report.poses[0].failure,
+        Some(Failure::MissingJoint(JointType::RightHand))
+    );
+    assert!(!report.evaluated());
+}
+
+#[test]
+fn explain_incomplete_template() {
+    let mut poses = dab_r();
+    poses
+        .get_mut(&Pose::DabR)
+        .unwrap()
+        .remove(&JointType::LeftHand);
+    let skeleton = skeleton(&identity_mock());
+    let report = Detector::with_poses(Settings::default(), poses).detect_explain(&skeleton.joints);
+    assert_eq!(
+        report.poses[0].failure,
+        Some(Failure::IncompleteTemplate(JointType::LeftHand))
+    );
+}
+
+#[test]
+fn invalid_config() {
+    let mut poses = dab_r();
+    assert!(Detector::try_with_poses(Settings::default(), poses.clone()).is_ok());
+    assert_eq!(
+        Detector::try_with_poses(Settings::default(), HashMap::new()).err(),
+        Some(Error::EmptyLibrary)
+    );
+
+    let settings = Settings {
+        joint_cutoff: -0.1,
+        ..Settings::default()
+    };
+    match Detector::try_with_poses(settings, poses.clone()) {
+        Err(Error::InvalidSettings(reason)) => assert!(reason.contains("joint_cutoff")),
+        _ => panic!("Negative cutoff accepted"),
+    }
+    let detector = Detector::default();
+    assert!(detector
+        .try_update_settings(|s| s.rotation_cutoff = -0.5)
+        .is_err());
+    assert!(detector
+        .try_update_settings(|s| s.joint_cutoff = 0.25)
+        .is_ok());
+    let settings = detector.settings();
+    assert_eq!(
+        settings.rotation_cutoff,
+        Settings::default().rotation_cutoff
+    );
+    assert_eq!(settings.joint_cutoff, 0.25);
+    let settings = Settings {
+        resolution: Some(Resolution::new(640, 0)),
+        ..Settings::default()
+    };
+    assert!(settings.validate().is_err());
+    let library = Library {
+        poses: poses.clone(),
+        resolution: Some(Resolution::new(0, 480)),
+    };
+    assert_eq!(
+        Detector::try_with_library(Settings::default(), library).err(),
+        Some(Error::InvalidResolution(Resolution::new(0, 480)))
+    );
+    let mut settings = Settings::default();
+    settings.overrides.insert(
+        Pose::Roof,
+        Cutoffs {
+            joint_cutoff: 0.1,
+            rotation_cutoff: f32::NAN,
+        },
+    );
+    assert!(settings.validate().is_err());
+
+    poses
+        .get_mut(&Pose::DabR)
+        .unwrap()
+        .remove(&JointType::RightElbow);
+    let detector = Detector::default();
+    assert_eq!(
+        detector
+            .try_replace(Settings::default(), Library::from(poses))
+            .err(),
+        Some(Error::IncompleteTemplate(Pose::DabR, JointType::RightElbow))
+    );
+    // The previous poses are kept
+    assert_eq!(detector.poses().len(), 6);
+}
+
+#[test]
+fn tester_results() {
+    let pose = dab_r().remove(&Pose::DabR).unwrap();
+    let tester = Tester::test_pose(Settings::default(), Pose::DabR, pose).unwrap();
+    let mut mock_skeleton = identity_mock();
+    assert_eq!(
+        tester.test(&skeleton(&mock_skeleton).joints),
+        Ok(Some(Pose::DabR))
+    );
+
+    mock_skeleton[7].1 = glm::vec2(0.9, 0.9);
+    assert_eq!(tester.test(&skeleton(&mock_skeleton).joints), Ok(None));
+
+    mock_skeleton.pop();
+    assert_eq!(
+        tester.test(&skeleton(&mock_skeleton).joints),
+        Err(Error::NotEvaluated(Failure::MissingJoint(
+            JointType::RightHand
+        )))
+    );
+}
+
+#[test]
+fn detector_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Detector>();
+}
+
+#[test]
+fn share_between_threads() {
+    let mut mock_skeleton = identity_mock();
+    mock_skeleton[5].1 = glm::vec2(0.3501515, 0.41818976);
+    let settings = Settings {
+        rotation_cutoff: 0.34,
+        joint_cutoff: 0.01,
+        ..Settings::default()
+    };
+    let skeleton = skeleton(&mock_skeleton);
+    let detector = Arc::new(Detector::with_poses(settings, dab_r()));
+
+    let worker = {
+        let detector = detector.clone();
+        let joints = skeleton.joints.clone();
+        thread::spawn(move || detector.detect(&joints))
+    };
+    assert_eq!(worker.join().unwrap(), Some(Pose::DabR));
+
+    detector.update_settings(|s| s.joint_cutoff = 0.00001);
+    assert_eq!(detector.detect(&skeleton.joints), None);
+
+    detector.set_poses(HashMap::new());
+    assert!(detector.poses().is_empty());
+    assert_eq!(detector.settings().joint_cutoff, 0.00001);
+}
+
+#[test]
+fn diagnose_joint_too_far() {
+    let mut mock_skeleton = identity_mock();
+    mock_skeleton[7].1 = glm::vec2(0.2777911, 0.30141133);
+    let settings = Settings {
+        rotation_cutoff: 0.34,
+        joint_cutoff: 0.01,
+        ..Settings::default()
+    };
+    let skeleton = skeleton(&mock_skeleton);
+    let detector = Detector::with_poses(settings, dab_r());
+    let report = detector.diagnose(Pose::DabR, &skeleton.joints).unwrap();
+    match report.failure {
+        Some(Failure::JointTooFar(JointType::RightHand, d)) => assert!(d > 0.01),
+        f => panic!("Unexpected failure {:?}", f),
+    }
+    let failed = report.failed_joints();
+    assert_eq!(failed[0].joint, JointType::RightHand);
+    assert!(failed.iter().all(|jd| jd.distance >= jd.cutoff));
+    match detector.diagnose(Pose::Roof, &skeleton.joints) {
+        Err(Error::UnknownPose(Pose::Roof)) => (),
+        r => panic!("Unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn diagnose_rotation_exceeded() {
+    let mock_skeleton: Vec<_> = identity_mock()
+        .into_iter()
+        .map(|(t, v)| (t, glm::vec2(v.y, 1.0 - v.x)))
+        .collect();
+    let skeleton = skeleton(&mock_skeleton);
+    let detector = Detector::with_poses(Settings::default(), dab_r());
+    let report = detector.diagnose(Pose::DabR, &skeleton.joints).unwrap();
+    match report.failure {
+        Some(Failure::RotationExceeded(r)) => {
+            assert!((r.abs() - std::f32::consts::FRAC_PI_2).abs() < 0.01)
+        }
+        f => panic!("Unexpected failure {:?}", f),
+    }
+}
+
+#[test]
+fn guidance_towards_template() {
+    let mut mock_skeleton = identity_mock();
+    mock_skeleton[7].1 = glm::vec2(0.1777911, 0.40141133);
+    let skeleton = skeleton(&mock_skeleton);
+    let detector = Detector::with_poses(Settings::default(), dab_r());
+    let corrections = detector
+        .guidance(Pose::DabR, &skeleton.joints)
+        .unwrap()
+        .unwrap();
+    assert_eq!(corrections.len(), 8);
+    let largest = &corrections[0];
+    assert_eq!(largest.joint, JointType::RightHand);
+    assert!(glm::distance(&largest.position, &mock_skeleton[7].1) < 0.0001);
+    // Template wants the hand back up
+    assert!(largest.displacement.y < -0.05);
+    assert!(corrections
+        .windows(2)
+        .all(|w| w[0].distance() >= w[1].distance()));
+    for c in &corrections {
+        assert!(glm::distance(&(c.position + c.displacement), &c.target) < 0.0001);
+    }
+}
+
+#[test]
+fn guidance_on_rotated_skeleton() {
+    let mock_skeleton = transform(&identity_mock(), 1.6, 0.8, glm::vec2(0.1, 0.0));
+    let skeleton = skeleton(&mock_skeleton);
+    let settings = Settings {
+        rotation_cutoff: 1.7,
+        ..Settings::default()
+    };
+    let detector = Detector::with_poses(settings, dab_r());
+    let corrections = detector
+        .guidance(Pose::DabR, &skeleton.joints)
+        .unwrap()
+        .unwrap();
+    assert_eq!(corrections.len(), 8);
+    // Already in the pose, just turned and further away
+    for c in &corrections {
+        assert!(c.distance() < 0.0001, "{:?}", c);
+        let (_, original) = mock_skeleton
+            .iter()
+            .find(|&&(t, _)| t == c.joint.id())
+            .unwrap();
+        assert!(glm::distance(&c.position, original) < 0.0001);
+    }
+}
+
+#[cfg(feature = "nuitrack")]
+#[test]
+fn detect_nuitrack_joints() {
+    use nuitrack_rs::{Orientation, SkeletonFeed, Vector3};
+
+    let joint = |type_, v: Vec2| nuitrack_rs::Joint {
+        type_,
+        confidence: 1.0,
+        orient: Orientation { matrix: [1.0; 9] },
+        proj: Vector3 {
+            x: v.x,
+            y: v.y,
+            z: 0.0,
+        },
+        real: Vector3 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        },
+    };
+    let mut joints: Vec<nuitrack_rs::Joint> = identity_mock()
+        .iter()
+        .map(|&(type_, v)| joint(type_, v))
+        .collect();
+    // Nuitrack's None joint is left out
+    joints.push(joint(0, glm::vec2(0.5, 0.5)));
+    let detector = Detector::with_poses(Settings::default(), dab_r());
+    assert_eq!(detector.detect(&joints), Some(Pose::DabR));
+
+    let feed = SkeletonFeed { id: 3, joints };
+    let converted = Skeleton::from(&feed);
+    assert_eq!(converted.id, 3);
+    assert_eq!(converted.joints.len(), 8);
+    assert_eq!(converted.joints[0].joint_type, JointType::LeftShoulder);
+    assert_eq!(detector.detect(&converted.joints), Some(Pose::DabR));
+}
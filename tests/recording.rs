@@ -0,0 +1,67 @@
+#![cfg(feature = "json")]
+
+use nalgebra_glm as glm;
+use nuitrack_pose_estimation as pe;
+
+mod common;
+
+use pe::recording::{Reader, Recorder};
+use pe::{Detector, Pose, Skeleton};
+
+fn skeleton(id: i32) -> Skeleton {
+    let mut skeleton = common::skeleton(Pose::DabR, id);
+    for joint in &mut skeleton.joints {
+        joint.confidence = 0.75;
+        joint.proj.z = 2.0;
+        joint.real = glm::vec3(joint.proj.x * 1000.0, joint.proj.y * 1000.0, 2000.0);
+    }
+    skeleton
+}
+
+#[test]
+fn record_and_replay() {
+    let mut recorder = Recorder::new(Vec::new());
+    recorder.write_at(0.0, &[skeleton(1)]).unwrap();
+    recorder
+        .write_at(0.5, vec![skeleton(1), skeleton(2)])
+        .unwrap();
+    let data = recorder.into_inner();
+
+    let frames: Vec<_> = Reader::new(&data[..]).collect::<Result<_, _>>().unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[1].timestamp, 0.5);
+    assert_eq!(frames[1].skeletons.len(), 2);
+    assert_eq!(frames[1].skeletons[1].id, 2);
+    for (read, written) in frames[0].skeletons[0].joints.iter().zip(skeleton(1).joints) {
+        assert_eq!(read.joint_type, written.joint_type);
+        assert_eq!(read.proj, written.proj);
+        assert_eq!(read.real, written.real);
+        assert_eq!(read.confidence, written.confidence);
+    }
+
+    let detector = Detector::default();
+    assert_eq!(frames[0].detect(&detector), vec![(1, Some(Pose::DabR))]);
+}
+
+#[test]
+fn reject_bad_lines() {
+    let data = "{\"timestamp\": 0.0, \"skeletons\": []}\nnot json\n";
+    let frames: Vec<_> = Reader::new(data.as_bytes()).collect();
+    assert!(frames[0].is_ok());
+    assert!(frames[1].is_err());
+}
+
+#[test]
+fn pose_library_round_trip() {
+    let library = pe::Library::default();
+    let text = pe::library::to_string(&library);
+    assert_eq!(pe::library::from_str(&text).unwrap(), library);
+
+    let poses = pe::Library::from(library.poses);
+    let text = pe::library::to_string(&poses);
+    assert!(!text.contains("resolution"));
+    assert_eq!(pe::library::from_str(&text).unwrap(), poses);
+
+    let unknown = r#"{"Dab": {"LeftShoulder": [0.5, 0.5]}}"#;
+    assert!(pe::library::from_str(unknown).is_err());
+}
@@ -0,0 +1,158 @@
+#![cfg(feature = "json")]
+
+use nuitrack_pose_estimation as pe;
+
+mod common;
+
+use pe::reload::Reloader;
+use pe::{Detector, Library, Pose, PoseData, Settings};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// A library of just this pose, stood like DabR
+fn library(pose: Pose) -> String {
+    let mut poses = PoseData::new();
+    poses.insert(pose, common::template(Pose::DabR));
+    pe::library::to_string(&Library::from(poses))
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(name);
+    fs::remove_file(&path).ok();
+    path
+}
+
+fn write_settings(path: &PathBuf, joint_cutoff: f32) {
+    let settings = Settings {
+        joint_cutoff,
+        ..Settings::default()
+    };
+    settings.save(path).unwrap();
+}
+
+#[test]
+fn reload_on_change() {
+    let settings_path = temp_path("npe_reload_settings.json");
+    let poses_path = temp_path("npe_reload_poses.json");
+    write_settings(&settings_path, 0.2);
+    fs::write(&poses_path, library(Pose::Roof)).unwrap();
+
+    let detector = Arc::new(Detector::default());
+    let mut reloader = Reloader::new(detector.clone());
+    reloader.watch_settings(&settings_path);
+    reloader.watch_poses(&poses_path);
+    assert!(reloader.poll().unwrap().is_ok());
+    assert_eq!(detector.settings().joint_cutoff, 0.2);
+    assert_eq!(
+        detector.poses().keys().collect::<Vec<_>>(),
+        vec![&Pose::Roof]
+    );
+    assert!(reloader.poll().is_none());
+
+    write_settings(&settings_path, 0.125);
+    assert!(reloader.poll().unwrap().is_ok());
+    assert_eq!(detector.settings().joint_cutoff, 0.125);
+    assert_eq!(detector.poses().len(), 1);
+
+    // An edit that keeps the same length is still seen
+    write_settings(&settings_path, 0.375);
+    assert!(reloader.poll().unwrap().is_ok());
+    assert_eq!(detector.settings().joint_cutoff, 0.375);
+}
+
+#[test]
+fn keep_concurrent_changes() {
+    let settings_path = temp_path("npe_concurrent_settings.json");
+    let poses_path = temp_path("npe_concurrent_poses.json");
+    write_settings(&settings_path, 0.2);
+    fs::write(&poses_path, library(Pose::Roof)).unwrap();
+
+    let detector = Arc::new(Detector::default());
+    let mut reloader = Reloader::new(detector.clone());
+    reloader.watch_settings(&settings_path);
+    reloader.watch_poses(&poses_path);
+    reloader.poll().unwrap().unwrap();
+
+    // Poses set elsewhere survive a settings reload
+    detector.set_poses(Detector::default().poses().as_ref().clone());
+    write_settings(&settings_path, 0.125);
+    reloader.poll().unwrap().unwrap();
+    assert_eq!(detector.settings().joint_cutoff, 0.125);
+    assert_eq!(detector.poses().len(), 6);
+
+    // And settings set elsewhere survive a library reload
+    detector.update_settings(|s| s.joint_cutoff = 0.0625);
+    fs::write(&poses_path, library(Pose::DabR)).unwrap();
+    reloader.poll().unwrap().unwrap();
+    assert_eq!(detector.settings().joint_cutoff, 0.0625);
+    assert_eq!(
+        detector.poses().keys().collect::<Vec<_>>(),
+        vec![&Pose::DabR]
+    );
+}
+
+#[test]
+fn reject_invalid_files() {
+    let settings_path = temp_path("npe_reject_settings.json");
+    let poses_path = temp_path("npe_reject_poses.json");
+    write_settings(&settings_path, 0.2);
+    fs::write(&poses_path, library(Pose::Roof)).unwrap();
+
+    let detector = Arc::new(Detector::default());
+    let mut reloader = Reloader::new(detector.clone());
+    reloader.watch_settings(&settings_path);
+    reloader.watch_poses(&poses_path);
+    reloader.poll().unwrap().unwrap();
+
+    // A valid settings file is still swapped in
+    // alongside a library that doesn't parse
+    write_settings(&settings_path, 0.35);
+    fs::write(&poses_path, "{\"Roof\": ").unwrap();
+    let error = reloader.poll().unwrap().unwrap_err().to_string();
+    assert!(error.contains("npe_reject_poses.json"), "{}", error);
+    assert_eq!(detector.settings().joint_cutoff, 0.35);
+    assert_eq!(detector.poses().len(), 1);
+    // Not tried again until it changes
+    assert!(reloader.poll().is_none());
+
+    write_settings(&settings_path, -1.25);
+    fs::write(&poses_path, "{}").unwrap();
+    let error = reloader.poll().unwrap().unwrap_err().to_string();
+    assert!(error.contains("joint_cutoff"), "{}", error);
+    assert!(error.contains("empty"), "{}", error);
+    assert_eq!(detector.settings().joint_cutoff, 0.35);
+    assert_eq!(detector.poses().len(), 1);
+
+    // Missing files keep the old config
+    fs::remove_file(&poses_path).unwrap();
+    assert!(reloader.poll().is_none());
+    assert_eq!(detector.poses().len(), 1);
+}
+
+#[test]
+fn watch_on_thread() {
+    let settings_path = temp_path("npe_watch_settings.json");
+    write_settings(&settings_path, 0.2);
+
+    let detector = Arc::new(Detector::default());
+    let mut reloader = Reloader::new(detector.clone());
+    reloader.watch_settings(&settings_path);
+    let watcher = reloader.spawn(Duration::from_millis(5));
+
+    let wait_for = |cutoff: f32| {
+        for _ in 0..200 {
+            if detector.settings().joint_cutoff == cutoff {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        false
+    };
+    assert!(wait_for(0.2));
+    write_settings(&settings_path, 0.0625);
+    assert!(wait_for(0.0625));
+    watcher.stop();
+}
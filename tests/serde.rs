@@ -0,0 +1,121 @@
+#![cfg(feature = "json")]
+
+use nuitrack_pose_estimation as pe;
+
+mod common;
+
+use pe::evaluate::Frame;
+use pe::osc::{Addresses, Arg, Message};
+use pe::recording::{Reader, RecordedFrame};
+use pe::tracker::Event;
+use pe::{Cutoffs, Detector, JointType, Pose, PoseData, Report, Settings};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+
+fn round_trip<T>(value: &T) -> T
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let json = serde_json::to_string(value).unwrap();
+    let back: T = serde_json::from_str(&json).unwrap();
+    assert_eq!(&back, value, "{}", json);
+    back
+}
+
+#[test]
+fn names_are_stable_strings() {
+    for &pose in Pose::ALL.iter() {
+        let json = serde_json::to_string(&pose).unwrap();
+        assert_eq!(json, format!("\"{}\"", pose.name()));
+        round_trip(&pose);
+    }
+    for &joint in JointType::ALL.iter() {
+        let json = serde_json::to_string(&joint).unwrap();
+        assert_eq!(json, format!("\"{}\"", joint.name()));
+        round_trip(&joint);
+    }
+}
+
+#[test]
+fn poses_and_settings_round_trip() {
+    let poses = PoseData::clone(&Detector::default().poses());
+    let json = serde_json::to_value(&poses).unwrap();
+    assert!(json["DabR"]["LeftShoulder"].is_array());
+    round_trip(&poses);
+
+    let mut settings = Settings::default();
+    settings.overrides.insert(
+        Pose::Roof,
+        Cutoffs {
+            joint_cutoff: 0.2,
+            rotation_cutoff: 0.4,
+        },
+    );
+    round_trip(&settings);
+}
+
+#[test]
+fn detections_round_trip() {
+    let skeleton = round_trip(&common::skeleton(Pose::DabR, 3));
+    let report = Detector::default().detect_explain(&skeleton.joints);
+    assert_eq!(report.pose, Some(Pose::DabR));
+
+    let json = serde_json::to_string(&report).unwrap();
+    let back: Report = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.pose, report.pose);
+    assert_eq!(serde_json::to_string(&back).unwrap(), json);
+    for (a, b) in back.poses.iter().zip(report.poses.iter()) {
+        assert_eq!(a.pose, b.pose);
+        assert_eq!(a.failure, b.failure);
+        assert_eq!(a.joints, b.joints);
+    }
+
+    let corrections = report.poses[0].corrections().unwrap();
+    assert_eq!(round_trip(&corrections), corrections);
+}
+
+#[test]
+fn written_like_recordings() {
+    let skeleton = common::skeleton(Pose::DabR, 3);
+    let frame = RecordedFrame {
+        timestamp: 0.5,
+        skeletons: vec![skeleton.clone()],
+    };
+    let line = serde_json::to_string(&frame).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(json["skeletons"][0]["joints"][0]["type"], "LeftShoulder");
+    let read = Reader::new(line.as_bytes()).next().unwrap().unwrap();
+    assert_eq!(read.skeletons, frame.skeletons);
+
+    let labelled = [
+        Frame {
+            label: Some(Pose::DabR),
+            joints: skeleton.joints.clone(),
+        },
+        Frame {
+            label: None,
+            joints: skeleton.joints.clone(),
+        },
+    ];
+    let lines: Vec<_> = labelled
+        .iter()
+        .map(|f| serde_json::to_string(f).unwrap())
+        .collect();
+    assert!(lines[1].contains("\"label\":\"none\""), "{}", lines[1]);
+    let read = pe::evaluate::read(lines.join("\n").as_bytes()).unwrap();
+    for (a, b) in read.iter().zip(labelled.iter()) {
+        assert_eq!(a.label, b.label);
+        assert_eq!(a.joints, b.joints);
+    }
+}
+
+#[test]
+fn messages_round_trip() {
+    round_trip(&Event::PoseStarted(2, Pose::Roof));
+    round_trip(&Addresses::default());
+    round_trip(&Message {
+        address: "/pose/1".to_string(),
+        args: vec![Arg::Str("DabR".to_string()), Arg::Float(0.5), Arg::Int(1)],
+    });
+}
@@ -0,0 +1,65 @@
+use nalgebra_glm as glm;
+use nuitrack_pose_estimation as pe;
+
+use pe::svg;
+use pe::{Chirality, Failure, JointDistance, Pose, PoseReport, Report, ARMS};
+
+fn pose_report() -> PoseReport {
+    let skeleton: Vec<_> = (0..8)
+        .map(|i| glm::vec2(i as f32 * 0.1 - 0.4, 0.0))
+        .collect();
+    let template: Vec<_> = (0..8)
+        .map(|i| glm::vec2(i as f32 * 0.1 - 0.4, 0.05))
+        .collect();
+    let joints = ARMS
+        .iter()
+        .map(|&joint| JointDistance {
+            joint,
+            distance: 0.05,
+            cutoff: 0.1,
+        })
+        .collect();
+    PoseReport {
+        pose: Pose::DabR,
+        skeleton,
+        template,
+        joints,
+        rotation: Some(0.1),
+        scale: Some(1.2),
+        centre: Some(glm::vec2(0.5, 0.5)),
+        chirality: Chirality::Same,
+        aspect: 1.0,
+        failure: None,
+    }
+}
+
+#[test]
+fn render_matched_pose() {
+    let svg = svg::render_pose(&pose_report());
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches("<circle").count(), 8);
+    assert_eq!(svg.matches("<line").count(), 14);
+    assert!(svg.contains("DabR"));
+    assert!(svg.contains("rotation 0.100"));
+    assert!(svg.contains("scale 1.200"));
+}
+
+#[test]
+fn render_report_panels() {
+    let mut missing = pose_report();
+    missing.pose = Pose::Roof;
+    missing.skeleton.clear();
+    missing.template.clear();
+    missing.joints.clear();
+    missing.failure = Some(Failure::MissingJoint(ARMS[0]));
+    let report = Report {
+        pose: Some(Pose::DabR),
+        poses: vec![pose_report(), missing],
+        rejected: None,
+    };
+    let svg = svg::render_report(&report);
+    assert_eq!(svg.matches("<g ").count(), 2);
+    assert_eq!(svg.matches("<circle").count(), 8);
+    assert!(svg.contains("MissingJoint(RightShoulder)"));
+}
@@ -0,0 +1,101 @@
+use nalgebra_glm as glm;
+use nuitrack_pose_estimation as pe;
+
+use pe::synth::{Clipping, Distortion, Generator};
+use pe::{Detector, Failure, JointType, Pose, Settings};
+
+#[test]
+fn identity_matches_template() {
+    let detector = Detector::default();
+    let poses = detector.poses();
+    let mut generator = Generator::new(1);
+    for (&pose, template) in poses.iter() {
+        let skeleton = generator.skeleton(template, &Distortion::default());
+        assert_eq!(skeleton.joints.len(), 8);
+        assert_eq!(detector.detect(&skeleton.joints), Some(pose));
+    }
+}
+
+#[test]
+fn random_similarity_transforms_match() {
+    let detector = Detector::new(Settings {
+        joint_cutoff: 0.02,
+        rotation_cutoff: 0.3,
+        ..Settings::default()
+    });
+    let poses = detector.poses();
+    let template = &poses[&Pose::Roof];
+    let limits = Distortion {
+        rotation: 0.25,
+        scale: 1.15,
+        translation: glm::vec2(0.05, 0.05),
+        noise: 0.001,
+        ..Distortion::default()
+    };
+    let mut generator = Generator::new(42);
+    for _ in 0..100 {
+        let distortion = generator.random_distortion(&limits);
+        let skeleton = generator.skeleton(template, &distortion);
+        let report = detector.detect_explain(&skeleton.joints);
+        assert_eq!(report.pose, Some(Pose::Roof), "{:?}", distortion);
+        // The alignment undoes the rotation that was applied
+        let rotation = report.poses[0].rotation.unwrap();
+        assert!(
+            (rotation - distortion.rotation).abs() < 0.01,
+            "{} {:?}",
+            rotation,
+            distortion
+        );
+    }
+}
+
+#[test]
+fn same_seed_same_skeletons() {
+    let poses = Detector::default().poses();
+    let template = &poses[&Pose::DabL];
+    let distortion = Distortion {
+        noise: 0.01,
+        drop_chance: 0.3,
+        ..Distortion::default()
+    };
+    let a = Generator::new(7).skeleton(template, &distortion);
+    let b = Generator::new(7).skeleton(template, &distortion);
+    assert_eq!(a.joints, b.joints);
+}
+
+#[test]
+fn dropped_and_clipped_joints() {
+    let detector = Detector::default();
+    let poses = detector.poses();
+    let template = &poses[&Pose::DabR];
+    let mut generator = Generator::new(3);
+
+    let dropped = Distortion {
+        dropped: vec![JointType::LeftElbow],
+        ..Distortion::default()
+    };
+    let skeleton = generator.skeleton(template, &dropped);
+    assert_eq!(skeleton.joints.len(), 7);
+    let report = detector.diagnose(Pose::DabR, &skeleton.joints).unwrap();
+    assert_eq!(
+        report.failure,
+        Some(Failure::MissingJoint(JointType::LeftElbow))
+    );
+
+    let off_frame = Distortion {
+        translation: glm::vec2(-0.3, 0.0),
+        clipping: Clipping::Drop,
+        ..Distortion::default()
+    };
+    let skeleton = generator.skeleton(template, &off_frame);
+    assert!(skeleton.joints.len() < 8);
+    assert!(skeleton.joints.iter().all(|j| j.proj.x >= 0.0));
+
+    let clamped = Distortion {
+        clipping: Clipping::Clamp,
+        ..off_frame
+    };
+    let skeleton = generator.skeleton(template, &clamped);
+    assert_eq!(skeleton.joints.len(), 8);
+    assert!(skeleton.joints.iter().any(|j| j.proj.x == 0.0));
+}
@@ -0,0 +1,136 @@
+#![cfg(feature = "websocket")]
+
+use nuitrack_pose_estimation as pe;
+
+mod common;
+
+use pe::tracker::Event;
+use pe::websocket::Server;
+use pe::{Detector, Pose};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Connect and return the handshake response's accept key
+fn connect(server: &Server) -> (TcpStream, String) {
+    let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    // Key and accept from RFC 6455
+    write!(
+        stream,
+        "GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n"
+    )
+    .unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut accept = String::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        if line.trim().is_empty() {
+            break;
+        }
+        // Header names aren't case sensitive
+        let mut parts = line.splitn(2, ':');
+        if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+            if name.eq_ignore_ascii_case("Sec-WebSocket-Accept") {
+                accept = value.trim().to_string();
+            }
+        }
+    }
+    (stream, accept)
+}
+
+/// Clients mask their frames
+fn send_text(stream: &mut TcpStream, text: &str) {
+    let mask = [0x12, 0x34, 0x56, 0x78];
+    let mut frame = vec![0x81];
+    if text.len() < 126 {
+        frame.push(0x80 | text.len() as u8);
+    } else {
+        frame.extend_from_slice(&[0x80 | 126, (text.len() >> 8) as u8, text.len() as u8]);
+    }
+    frame.extend_from_slice(&mask);
+    frame.extend(text.bytes().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    stream.write_all(&frame).unwrap();
+}
+
+fn read_text(stream: &mut TcpStream) -> serde_json::Value {
+    let mut head = [0; 2];
+    stream.read_exact(&mut head).unwrap();
+    assert_eq!(head[0], 0x81);
+    let len = match head[1] {
+        126 => {
+            let mut len = [0; 2];
+            stream.read_exact(&mut len).unwrap();
+            (len[0] as usize) << 8 | len[1] as usize
+        }
+        len => len as usize,
+    };
+    let mut payload = vec![0; len];
+    stream.read_exact(&mut payload).unwrap();
+    serde_json::from_slice(&payload).unwrap()
+}
+
+#[test]
+fn broadcast_frames_and_events() {
+    let mut server = Server::bind("127.0.0.1:0", Arc::new(Detector::default())).unwrap();
+    let (mut client, accept) = connect(&server);
+    assert_eq!(accept, "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+
+    let events = server.send_frame(&[common::skeleton(Pose::DabR, 2)]);
+    assert_eq!(
+        events,
+        vec![Event::UserFound(2), Event::PoseStarted(2, Pose::DabR)]
+    );
+    let frame = read_text(&mut client);
+    assert_eq!(frame["type"], "frame");
+    assert_eq!(frame["detections"][0]["user"], 2);
+    assert_eq!(frame["detections"][0]["pose"], "DabR");
+    assert_eq!(frame["detections"][0]["chirality"], "Same");
+    assert!(frame["detections"][0]["scores"]["DabR"].as_f64().unwrap() > 0.9);
+    assert_eq!(frame["events"][1]["event"], "started");
+    assert_eq!(frame["events"][1]["pose"], "DabR");
+
+    server.send_frame(&[]);
+    let frame = read_text(&mut client);
+    assert_eq!(frame["events"][0]["event"], "ended");
+    assert_eq!(frame["events"][1]["event"], "lost");
+}
+
+#[test]
+fn control_messages() {
+    let detector = Arc::new(Detector::default());
+    let server = Server::bind("127.0.0.1:0", detector.clone()).unwrap();
+    let (mut client, _) = connect(&server);
+
+    send_text(&mut client, r#"{"type": "settings", "joint_cutoff": 0.05}"#);
+    assert_eq!(read_text(&mut client)["type"], "ok");
+    assert_eq!(detector.settings().joint_cutoff, 0.05);
+    assert_eq!(detector.settings().rotation_cutoff, 0.22);
+
+    let roof = &detector.poses()[&Pose::Roof];
+    let library: Vec<String> = roof
+        .iter()
+        .map(|(joint, v)| format!(r#""{}": [{}, {}]"#, joint.name(), v.x, v.y))
+        .collect();
+    send_text(
+        &mut client,
+        &format!(
+            r#"{{"type": "poses", "library": {{"Roof": {{{}}}}}}}"#,
+            library.join(", ")
+        ),
+    );
+    assert_eq!(read_text(&mut client)["type"], "ok");
+    assert_eq!(detector.poses().len(), 1);
+    assert!(detector.poses().contains_key(&Pose::Roof));
+
+    // Files on the server can't be loaded
+    send_text(&mut client, r#"{"type": "poses", "path": "library.json"}"#);
+    let reply = read_text(&mut client);
+    assert_eq!(reply["type"], "error");
+    assert_eq!(detector.poses().len(), 1);
+}